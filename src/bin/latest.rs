@@ -1,16 +1,23 @@
-use aoc_2025::days::ALL_DAYS;
+use std::{env, process};
+
+use aoc_2025::{
+    days::{self, ALL_DAYS},
+    options,
+};
 
 fn main() {
-    let n = ALL_DAYS.len();
-    let (pt1, pt2) = ALL_DAYS.last().unwrap();
+    let args: Vec<String> = env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(|a| a.as_str()).collect();
+    let error = match options::init(&args) {
+        Ok(rest) if rest.is_empty() => None,
+        Ok(rest) => Some(format!("unexpected argument {}", rest[0])),
+        Err(e) => Some(e),
+    };
+    if let Some(e) = error {
+        eprintln!("\x1b[31merror:\x1b[m {}", e);
+        eprintln!("usage: latest {}", options::USAGE);
+        process::exit(2);
+    }
 
-    println!("\x1b[30m========\x1b[m");
-    println!("\x1b[32m# DAY {:02}\x1b[m", n);
-    println!("\x1b[30m--------\x1b[m");
-    println!("\x1b[34m## Part 1\x1b[m");
-    pt1();
-    println!("\x1b[30m--------\x1b[m");
-    println!("\x1b[34m## Part 2\x1b[m");
-    pt2();
-    println!();
+    days::run(ALL_DAYS.len());
 }
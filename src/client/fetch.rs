@@ -0,0 +1,106 @@
+use std::{fs, path::PathBuf};
+
+use super::{check_unlocked, now, Client, Error};
+
+impl Client {
+    pub fn input_path(&self, day: u8) -> PathBuf {
+        self.inputs_dir.join(format!("input_{:02}.txt", day))
+    }
+
+    /// Downloads the input of `day` unless it is already on disk, and returns
+    /// the path of the input file.
+    pub fn fetch_input(&self, day: u8) -> Result<PathBuf, Error> {
+        self.fetch_input_at(day, now())
+    }
+
+    fn fetch_input_at(&self, day: u8, now: u64) -> Result<PathBuf, Error> {
+        check_unlocked(day, now)?;
+
+        let path = self.input_path(day);
+        if path.exists() {
+            // inputs never change, don't bother the server again
+            return Ok(path);
+        }
+
+        let input = self
+            .agent
+            .get(&self.day_url(day, "/input"))
+            .set("Cookie", &self.cookie())
+            .call()?
+            .into_string()?;
+
+        // write then rename so that an interrupted download doesn't end up in the cache
+        fs::create_dir_all(&self.inputs_dir)?;
+        let partial = path.with_extension("part");
+        fs::write(&partial, input)?;
+        fs::rename(&partial, &path)?;
+
+        Ok(path)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use super::super::{stub, Client, Error, FIRST_UNLOCK};
+
+    const DAY_5: u64 = FIRST_UNLOCK + 4 * 24 * 3600;
+
+    #[test]
+    fn test_fetch() {
+        let dir = stub::temp_dir("fetch");
+        let (url, server) = stub::serve_once("200 OK", "3-5\n10-14\n");
+        let client = Client::new(&url, "c0ffee\n", dir.clone());
+
+        let path = client.fetch_input_at(5, DAY_5).unwrap();
+        assert_eq!(path, dir.join("input_05.txt"));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "3-5\n10-14\n");
+
+        let request = server.join().unwrap();
+        assert!(request.starts_with("GET /2025/day/5/input HTTP/1.1\r\n"));
+        assert!(request.contains("Cookie: session=c0ffee\r\n"));
+        assert!(request.contains("User-Agent: github.com/blinxe/aoc-2025 "));
+    }
+
+    #[test]
+    fn test_fetch_cached() {
+        let dir = stub::temp_dir("fetch-cached");
+        fs::write(dir.join("input_05.txt"), "cached").unwrap();
+        // nothing listens there: any request would fail
+        let client = Client::new("http://127.0.0.1:1", "c0ffee", dir.clone());
+
+        let path = client.fetch_input_at(5, DAY_5).unwrap();
+        assert_eq!(fs::read_to_string(path).unwrap(), "cached");
+    }
+
+    #[test]
+    fn test_fetch_locked() {
+        let dir = stub::temp_dir("fetch-locked");
+        let client = Client::new("http://127.0.0.1:1", "c0ffee", dir.clone());
+
+        match client.fetch_input_at(6, DAY_5 + 3600) {
+            Err(Error::Locked { day: 6, wait }) => assert_eq!(wait.as_secs(), 23 * 3600),
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(matches!(
+            client.fetch_input_at(13, DAY_5),
+            Err(Error::InvalidDay(13))
+        ));
+        assert!(!dir.join("input_06.txt").exists());
+    }
+
+    #[test]
+    fn test_fetch_error() {
+        let dir = stub::temp_dir("fetch-error");
+        let (url, server) = stub::serve_once("400 Bad Request", "Please log in.");
+        let client = Client::new(&url, "expired", dir.clone());
+
+        match client.fetch_input_at(5, DAY_5) {
+            Err(Error::Status(400, body)) => assert_eq!(body, "Please log in."),
+            other => panic!("unexpected {:?}", other),
+        }
+        server.join().unwrap();
+        assert!(!dir.join("input_05.txt").exists());
+    }
+}
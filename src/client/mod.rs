@@ -0,0 +1,230 @@
+use std::{
+    env, fmt, fs, io,
+    path::PathBuf,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+mod fetch;
+mod submit;
+
+pub use submit::{Outcome, Verdict};
+
+pub const YEAR: u16 = 2025;
+pub const DAYS: u8 = 12;
+
+/// Unix timestamp of the day 1 unlock: 2025-12-01 00:00 EST.
+const FIRST_UNLOCK: u64 = 1_764_565_200;
+
+const DEFAULT_BASE_URL: &str = "https://adventofcode.com";
+const DEFAULT_SESSION_FILE: &str = ".session";
+const USER_AGENT: &str = concat!(
+    "github.com/blinxe/aoc-2025 ",
+    env!("CARGO_PKG_NAME"),
+    "/",
+    env!("CARGO_PKG_VERSION")
+);
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Http(String),
+    Status(u16, String),
+    NoSession(PathBuf),
+    InvalidDay(u8),
+    InvalidPart(u8),
+    Locked { day: u8, wait: Duration },
+    Solved(String),
+    Guessed(String, Verdict),
+    Throttled(Duration),
+    Unexpected(String),
+}
+
+fn fmt_duration(d: Duration) -> String {
+    let secs = d.as_secs();
+    if secs >= 3600 {
+        format!("{}h {:02}m {:02}s", secs / 3600, secs / 60 % 60, secs % 60)
+    } else if secs >= 60 {
+        format!("{}m {:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "I/O error: {}", e),
+            Error::Http(e) => write!(f, "HTTP error: {}", e),
+            Error::Status(code, body) => write!(f, "server answered {}: {}", code, body.trim()),
+            Error::NoSession(path) => write!(
+                f,
+                "no session token found in {} (copy the `session` cookie from the website)",
+                path.display()
+            ),
+            Error::InvalidDay(day) => write!(f, "there is no day {} in {}", day, YEAR),
+            Error::InvalidPart(part) => write!(f, "there is no part {}", part),
+            Error::Locked { day, wait } => write!(
+                f,
+                "day {} is not unlocked yet ({} left)",
+                day,
+                fmt_duration(*wait)
+            ),
+            Error::Solved(answer) => write!(f, "already solved, the answer was {}", answer),
+            Error::Guessed(answer, verdict) => {
+                write!(f, "{} was already guessed and was {}", answer, verdict)
+            }
+            Error::Throttled(wait) => write!(
+                f,
+                "submitted too recently, try again in {}",
+                fmt_duration(*wait)
+            ),
+            Error::Unexpected(body) => write!(f, "unexpected answer from the server: {}", body),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<ureq::Error> for Error {
+    fn from(e: ureq::Error) -> Self {
+        match e {
+            ureq::Error::Status(code, response) => {
+                Error::Status(code, response.into_string().unwrap_or_default())
+            }
+            ureq::Error::Transport(t) => Error::Http(t.to_string()),
+        }
+    }
+}
+
+/// Talks to the puzzle website on behalf of the session owner.
+///
+/// The base URL can be overridden with `AOC_BASE_URL` and the session token
+/// file with `AOC_SESSION_FILE`, which is how the client is pointed at a
+/// local stub server.
+pub struct Client {
+    agent: ureq::Agent,
+    base_url: String,
+    session: String,
+    inputs_dir: PathBuf,
+}
+
+impl Client {
+    pub fn new(base_url: &str, session: &str, inputs_dir: PathBuf) -> Self {
+        let agent = ureq::AgentBuilder::new()
+            .user_agent(USER_AGENT)
+            .timeout(Duration::from_secs(30))
+            .build();
+
+        Client {
+            agent,
+            base_url: base_url.trim_end_matches('/').to_owned(),
+            session: session.trim().to_owned(),
+            inputs_dir,
+        }
+    }
+
+    pub fn from_env() -> Result<Self, Error> {
+        let base_url = env::var("AOC_BASE_URL").unwrap_or(DEFAULT_BASE_URL.to_owned());
+        let session_file =
+            PathBuf::from(env::var("AOC_SESSION_FILE").unwrap_or(DEFAULT_SESSION_FILE.to_owned()));
+        let session = match fs::read_to_string(&session_file) {
+            Ok(s) if !s.trim().is_empty() => s,
+            _ => return Err(Error::NoSession(session_file)),
+        };
+
+        Ok(Client::new(&base_url, &session, PathBuf::from("inputs")))
+    }
+
+    fn day_url(&self, day: u8, path: &str) -> String {
+        format!("{}/{}/day/{}{}", self.base_url, YEAR, day, path)
+    }
+
+    fn cookie(&self) -> String {
+        format!("session={}", self.session)
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+/// Checks that `day` exists and has been unlocked at unix time `now`.
+fn check_unlocked(day: u8, now: u64) -> Result<(), Error> {
+    if day == 0 || day > DAYS {
+        return Err(Error::InvalidDay(day));
+    }
+    let unlock = FIRST_UNLOCK + (day as u64 - 1) * 24 * 3600;
+    if now < unlock {
+        return Err(Error::Locked {
+            day,
+            wait: Duration::from_secs(unlock - now),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+pub(crate) mod stub {
+    use std::{
+        io::{BufRead, BufReader, Read, Write},
+        net::TcpListener,
+        path::PathBuf,
+        thread::{self, JoinHandle},
+    };
+
+    /// Serves a single HTTP request with `status` and `body`, and hands back
+    /// the raw request that was received.
+    pub fn serve_once(status: &'static str, body: &'static str) -> (String, JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let handle = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request = String::new();
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if let Some(len) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                    content_length = len.trim().parse().unwrap();
+                }
+                request.push_str(&line);
+                if line == "\r\n" || line.is_empty() {
+                    break;
+                }
+            }
+            let mut payload = vec![0; content_length];
+            reader.read_exact(&mut payload).unwrap();
+            request.push_str(&String::from_utf8(payload).unwrap());
+
+            write!(
+                stream,
+                "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            )
+            .unwrap();
+            request
+        });
+
+        (url, handle)
+    }
+
+    /// A fresh, empty directory under the system temp dir.
+    pub fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("aoc-2025-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+}
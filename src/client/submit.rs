@@ -0,0 +1,344 @@
+use std::{fmt, fs, path::PathBuf, time::Duration};
+
+use regex::Regex;
+
+use super::{check_unlocked, now, Client, Error};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Right,
+    Wrong,
+    TooHigh,
+    TooLow,
+}
+
+impl Verdict {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Verdict::Right => "right",
+            Verdict::Wrong => "wrong",
+            Verdict::TooHigh => "high",
+            Verdict::TooLow => "low",
+        }
+    }
+
+    fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "right" => Some(Verdict::Right),
+            "wrong" => Some(Verdict::Wrong),
+            "high" => Some(Verdict::TooHigh),
+            "low" => Some(Verdict::TooLow),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Verdict::Right => write!(f, "right"),
+            Verdict::Wrong => write!(f, "wrong"),
+            Verdict::TooHigh => write!(f, "too high"),
+            Verdict::TooLow => write!(f, "too low"),
+        }
+    }
+}
+
+/// What the server made of a submission.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Answered(Verdict),
+    RateLimited(Duration),
+    AlreadySolved,
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Outcome::Answered(Verdict::Right) => write!(f, "That's the right answer!"),
+            Outcome::Answered(verdict) => write!(f, "That's not the right answer ({}).", verdict),
+            Outcome::RateLimited(wait) => write!(
+                f,
+                "Answered too recently, {} left to wait.",
+                super::fmt_duration(*wait)
+            ),
+            Outcome::AlreadySolved => write!(f, "This part is already solved."),
+        }
+    }
+}
+
+/// One line of the guess history: `<part> <verdict> <answer>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Guess {
+    part: u8,
+    verdict: Verdict,
+    answer: String,
+}
+
+fn parse_history(history: &str) -> Vec<Guess> {
+    history
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(3, ' ');
+            let part = fields.next()?.parse().ok()?;
+            let verdict = Verdict::from_str(fields.next()?)?;
+            let answer = fields.next()?.to_owned();
+            Some(Guess {
+                part,
+                verdict,
+                answer,
+            })
+        })
+        .collect()
+}
+
+/// Refuses answers that are already known to be wrong, without asking the server.
+fn check_guess(history: &[Guess], part: u8, answer: &str) -> Result<(), Error> {
+    let guesses = || history.iter().filter(|g| g.part == part);
+
+    if let Some(right) = guesses().find(|g| g.verdict == Verdict::Right) {
+        return Err(Error::Solved(right.answer.clone()));
+    }
+    if let Some(guess) = guesses().find(|g| g.answer == answer) {
+        return Err(Error::Guessed(guess.answer.clone(), guess.verdict));
+    }
+
+    let Ok(value) = answer.parse::<i128>() else {
+        return Ok(());
+    };
+    for guess in guesses() {
+        let Ok(bound) = guess.answer.parse::<i128>() else {
+            continue;
+        };
+        match guess.verdict {
+            Verdict::TooHigh if value >= bound => {
+                return Err(Error::Guessed(guess.answer.clone(), guess.verdict));
+            }
+            Verdict::TooLow if value <= bound => {
+                return Err(Error::Guessed(guess.answer.clone(), guess.verdict));
+            }
+            _ => (),
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the outcome, and how long to wait before the next submission.
+fn parse_response(html: &str) -> Option<(Outcome, u64)> {
+    if html.contains("That's the right answer") {
+        return Some((Outcome::Answered(Verdict::Right), 0));
+    }
+
+    if html.contains("That's not the right answer") {
+        let verdict = if html.contains("your answer is too high") {
+            Verdict::TooHigh
+        } else if html.contains("your answer is too low") {
+            Verdict::TooLow
+        } else {
+            Verdict::Wrong
+        };
+        let re = Regex::new(r"[Pp]lease wait (one|\d+) minutes? before trying again").unwrap();
+        let wait = re.captures(html).map_or(60, |cap| match &cap[1] {
+            "one" => 60,
+            n => n.parse::<u64>().unwrap() * 60,
+        });
+        return Some((Outcome::Answered(verdict), wait));
+    }
+
+    if html.contains("You gave an answer too recently") {
+        let re = Regex::new(r"You have (?:(\d+)m )?(\d+)s left to wait").unwrap();
+        let wait = re.captures(html).map_or(60, |cap| {
+            let mins: u64 = cap.get(1).map_or(0, |m| m.as_str().parse().unwrap());
+            let secs: u64 = cap[2].parse().unwrap();
+            mins * 60 + secs
+        });
+        return Some((Outcome::RateLimited(Duration::from_secs(wait)), wait));
+    }
+
+    if html.contains("You don't seem to be solving the right level") {
+        return Some((Outcome::AlreadySolved, 0));
+    }
+
+    None
+}
+
+impl Client {
+    fn history_path(&self, day: u8) -> PathBuf {
+        self.inputs_dir.join(format!("guesses_{:02}.txt", day))
+    }
+
+    fn throttle_path(&self) -> PathBuf {
+        self.inputs_dir.join(".throttle")
+    }
+
+    /// Posts `answer` for the given part, unless the guess history or the
+    /// local throttle already tell that it would be pointless.
+    pub fn submit(&self, day: u8, part: u8, answer: &str) -> Result<Outcome, Error> {
+        self.submit_at(day, part, answer, now())
+    }
+
+    fn submit_at(&self, day: u8, part: u8, answer: &str, now: u64) -> Result<Outcome, Error> {
+        check_unlocked(day, now)?;
+        if part != 1 && part != 2 {
+            return Err(Error::InvalidPart(part));
+        }
+        let answer = answer.trim();
+
+        let history_path = self.history_path(day);
+        let history = fs::read_to_string(&history_path).unwrap_or_default();
+        check_guess(&parse_history(&history), part, answer)?;
+
+        let throttle_path = self.throttle_path();
+        let not_before: u64 = fs::read_to_string(&throttle_path)
+            .ok()
+            .and_then(|t| t.trim().parse().ok())
+            .unwrap_or(0);
+        if now < not_before {
+            return Err(Error::Throttled(Duration::from_secs(not_before - now)));
+        }
+
+        let html = self
+            .agent
+            .post(&self.day_url(day, "/answer"))
+            .set("Cookie", &self.cookie())
+            .send_form(&[("level", &part.to_string()), ("answer", answer)])?
+            .into_string()?;
+        let (outcome, wait) =
+            parse_response(&html).ok_or(Error::Unexpected(html.chars().take(200).collect()))?;
+
+        fs::create_dir_all(&self.inputs_dir)?;
+        if wait > 0 {
+            fs::write(&throttle_path, (now + wait).to_string())?;
+        }
+        if let Outcome::Answered(verdict) = outcome {
+            let guess = format!("{} {} {}\n", part, verdict.as_str(), answer);
+            fs::write(&history_path, history + &guess)?;
+        }
+
+        Ok(outcome)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{fs, time::Duration};
+
+    use super::super::{stub, Client, Error, FIRST_UNLOCK};
+    use super::{check_guess, parse_history, parse_response, Outcome, Verdict};
+
+    const DAY_3: u64 = FIRST_UNLOCK + 2 * 24 * 3600;
+
+    #[test]
+    fn test_parse_response() {
+        let right = "<article><p>That's the right answer!  You are <em>one gold star</em> closer.</p></article>";
+        let high = "<article><p>That's not the right answer; your answer is too high.  If you're stuck, make sure you're using the full input data. Please wait one minute before trying again. [<a href=\"/2025/day/3\">Return to Day 3</a>]</p></article>";
+        let low = "<article><p>That's not the right answer; your answer is too low.  Please wait 5 minutes before trying again.</p></article>";
+        let wrong = "<article><p>That's not the right answer.  If you're stuck, make sure you're using the full input data.</p></article>";
+        let limited = "<article><p>You gave an answer too recently; you have to wait after submitting an answer before trying again.  You have 1m 6s left to wait.</p></article>";
+        let limited_secs = "<article><p>You gave an answer too recently; you have to wait after submitting an answer before trying again.  You have 42s left to wait.</p></article>";
+        let solved = "<article><p>You don't seem to be solving the right level.  Did you already complete it?</p></article>";
+
+        assert_eq!(
+            parse_response(right),
+            Some((Outcome::Answered(Verdict::Right), 0))
+        );
+        assert_eq!(
+            parse_response(high),
+            Some((Outcome::Answered(Verdict::TooHigh), 60))
+        );
+        assert_eq!(
+            parse_response(low),
+            Some((Outcome::Answered(Verdict::TooLow), 300))
+        );
+        assert_eq!(
+            parse_response(wrong),
+            Some((Outcome::Answered(Verdict::Wrong), 60))
+        );
+        assert_eq!(
+            parse_response(limited),
+            Some((Outcome::RateLimited(Duration::from_secs(66)), 66))
+        );
+        assert_eq!(
+            parse_response(limited_secs),
+            Some((Outcome::RateLimited(Duration::from_secs(42)), 42))
+        );
+        assert_eq!(parse_response(solved), Some((Outcome::AlreadySolved, 0)));
+        assert_eq!(parse_response("<html>Oops</html>"), None);
+    }
+
+    #[test]
+    fn test_check_guess() {
+        let history = parse_history("1 high 500\n1 low 100\n1 wrong 250\n2 right 42\n");
+
+        assert!(check_guess(&history, 1, "300").is_ok());
+        assert!(check_guess(&history, 1, "abc").is_ok());
+        assert!(matches!(
+            check_guess(&history, 1, "250"),
+            Err(Error::Guessed(_, Verdict::Wrong))
+        ));
+        assert!(matches!(
+            check_guess(&history, 1, "501"),
+            Err(Error::Guessed(_, Verdict::TooHigh))
+        ));
+        assert!(matches!(
+            check_guess(&history, 1, "100"),
+            Err(Error::Guessed(_, Verdict::TooLow))
+        ));
+        assert!(matches!(check_guess(&history, 2, "43"), Err(Error::Solved(a)) if a == "42"));
+    }
+
+    #[test]
+    fn test_submit() {
+        let dir = stub::temp_dir("submit");
+        let (url, server) = stub::serve_once(
+            "200 OK",
+            "<article><p>That's not the right answer; your answer is too low.  Please wait one minute before trying again.</p></article>",
+        );
+        let client = Client::new(&url, "c0ffee", dir.clone());
+
+        let outcome = client.submit_at(3, 1, "357", DAY_3).unwrap();
+        assert_eq!(outcome, Outcome::Answered(Verdict::TooLow));
+
+        let request = server.join().unwrap();
+        assert!(request.starts_with("POST /2025/day/3/answer HTTP/1.1\r\n"));
+        assert!(request.contains("Cookie: session=c0ffee\r\n"));
+        assert!(request.ends_with("level=1&answer=357"));
+
+        assert_eq!(
+            fs::read_to_string(dir.join("guesses_03.txt")).unwrap(),
+            "1 low 357\n"
+        );
+        // the same guess is never sent again, nor anything below it
+        assert!(matches!(
+            client.submit_at(3, 1, "300", DAY_3 + 3600),
+            Err(Error::Guessed(_, Verdict::TooLow))
+        ));
+        // and the server asked to wait a minute
+        assert!(matches!(
+            client.submit_at(3, 1, "400", DAY_3 + 30),
+            Err(Error::Throttled(wait)) if wait.as_secs() == 30
+        ));
+    }
+
+    #[test]
+    fn test_submit_rate_limited() {
+        let dir = stub::temp_dir("submit-limited");
+        let (url, server) = stub::serve_once(
+            "200 OK",
+            "<article><p>You gave an answer too recently; you have to wait after submitting an answer before trying again.  You have 2m 0s left to wait.</p></article>",
+        );
+        let client = Client::new(&url, "c0ffee", dir.clone());
+
+        let outcome = client.submit_at(3, 2, "3121910778619", DAY_3).unwrap();
+        assert_eq!(outcome, Outcome::RateLimited(Duration::from_secs(120)));
+        server.join().unwrap();
+
+        // not a guess: nothing is recorded, but the throttle is armed
+        assert!(!dir.join("guesses_03.txt").exists());
+        assert!(matches!(
+            client.submit_at(3, 2, "3121910778619", DAY_3 + 60),
+            Err(Error::Throttled(wait)) if wait.as_secs() == 60
+        ));
+    }
+}
@@ -1,21 +1,53 @@
-use crate::utils::input::read_input;
+use std::fmt;
 
-fn parse_input(input: &str) -> Vec<i32> {
+use super::DayError;
+use crate::utils::{
+    input::read_input,
+    options,
+    parse::{signed_by_prefix, ParseError},
+};
+
+/// One rotation of the dial, and how many times it went through 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Step {
+    rotation: i32,
+    start: i32,
+    end: i32,
+    /// The clicks landing on 0, the last one included.
+    zero_passes: i32,
+}
+
+impl Step {
+    fn on_zero(&self) -> bool {
+        self.end == 0
+    }
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let dir = if self.rotation < 0 { 'L' } else { 'R' };
+        write!(
+            f,
+            "{}{:<5} {:>2} -> {:>2}  passes 0 {} time(s){}",
+            dir,
+            self.rotation.abs(),
+            self.start,
+            self.end,
+            self.zero_passes,
+            if self.on_zero() { ", landed on 0" } else { "" }
+        )
+    }
+}
+
+fn parse_input(input: &str) -> Result<Vec<i32>, ParseError> {
     input
         .lines()
-        .map(|l| {
-            let num: i32 = l[1..].parse().unwrap();
-            match l.chars().next().unwrap() {
-                'R' => num,
-                'L' => -num,
-                _ => panic!("invalid input"),
-            }
-        })
+        .map(|l| signed_by_prefix(l, 'R', 'L'))
         .collect()
 }
 
-fn solve_part_1(input: &str) {
-    let rotations = parse_input(input);
+fn solve_part_1(input: &str) -> Result<i32, ParseError> {
+    let rotations = parse_input(input)?;
     let mut count = 0;
     let mut value = 50;
 
@@ -27,33 +59,98 @@ fn solve_part_1(input: &str) {
     }
 
     println!("Number of 0s: {}", count);
+
+    Ok(count)
 }
 
-fn solve_part_2(input: &str) {
-    let rotations = parse_input(input);
-    let mut count = 0;
+fn trace(rotations: &[i32]) -> Vec<Step> {
     let mut value = 50;
+    rotations
+        .iter()
+        .map(|&num| {
+            let mut zero_passes = num.abs() / 100;
+            let rem = num % 100;
+            if value != 0 && (value + rem <= 0 || value + rem > 99) {
+                zero_passes += 1;
+            }
+            let start = value;
+            value = (value + rem + 100) % 100;
+            Step {
+                rotation: num,
+                start,
+                end: value,
+                zero_passes,
+            }
+        })
+        .collect()
+}
 
-    for num in rotations {
-        count += num.abs() / 100;
-        let rem = num % 100;
-        if value != 0 && (value + rem <= 0 || value + rem > 99) {
-            count += 1;
+/// Rows and columns from the centre of the dial to its ring.
+const DIAL_RADIUS: (f64, f64) = (5.0, 10.0);
+
+/// A small picture of the dial during `step`: its ring of positions with 0 at
+/// the top, the start as `o` and the end as `@`.
+fn dial(step: &Step) -> String {
+    let (rows, cols) = (
+        2 * DIAL_RADIUS.0 as usize + 1,
+        2 * DIAL_RADIUS.1 as usize + 1,
+    );
+    let mut canvas = vec![vec![' '; cols]; rows];
+    let mut mark = |value: i32, c: char| {
+        let angle = value as f64 / 100.0 * std::f64::consts::TAU;
+        let row = DIAL_RADIUS.0 - (DIAL_RADIUS.0 * angle.cos()).round();
+        let col = DIAL_RADIUS.1 + (DIAL_RADIUS.1 * angle.sin()).round();
+        canvas[row as usize][col as usize] = c;
+    };
+
+    for value in 0..100 {
+        mark(value, '.');
+    }
+    mark(0, '0');
+    mark(step.start, 'o');
+    mark(step.end, '@');
+
+    let mut picture = format!("{}\n", step);
+    for row in canvas {
+        let line: String = row.into_iter().collect();
+        picture.push_str(line.trim_end());
+        picture.push('\n');
+    }
+    picture
+}
+
+fn solve_part_2(input: &str) -> Result<i32, ParseError> {
+    let rotations = parse_input(input)?;
+    let steps = trace(&rotations);
+    let count = steps.iter().map(|s| s.zero_passes).sum();
+
+    let opts = options::get();
+    if opts.trace {
+        for step in steps.iter() {
+            println!("{}", step);
         }
-        value = (value + rem + 100) % 100;
+    }
+    for step in steps.iter().take(opts.dial.unwrap_or(0)) {
+        print!("{}", dial(step));
     }
 
     println!("Number of 0s: {}", count);
+
+    Ok(count)
 }
 
-pub fn part_1() {
+pub fn part_1() -> Result<String, DayError> {
     let input = read_input(module_path!());
-    solve_part_1(input.as_str());
+    solve_part_1(input.as_str())
+        .map(|answer| answer.to_string())
+        .map_err(|e| e.locate(&input).into())
 }
 
-pub fn part_2() {
+pub fn part_2() -> Result<String, DayError> {
     let input = read_input(module_path!());
-    solve_part_2(input.as_str());
+    solve_part_2(input.as_str())
+        .map(|answer| answer.to_string())
+        .map_err(|e| e.locate(&input).into())
 }
 
 #[cfg(test)]
@@ -75,13 +172,61 @@ mod test {
 
     #[test]
     fn test_part_1() {
-        super::solve_part_1(EXAMPLE_1);
+        super::solve_part_1(EXAMPLE_1).unwrap();
     }
 
     const EXAMPLE_2: &str = EXAMPLE_1;
 
     #[test]
     fn test_part_2() {
-        super::solve_part_2(EXAMPLE_2);
+        super::solve_part_2(EXAMPLE_2).unwrap();
+    }
+
+    #[test]
+    fn test_trace() {
+        let steps = super::trace(&[-68, -30, 48, 1000, -50, 150, -250]);
+        let passes: Vec<(i32, i32, i32)> = steps
+            .iter()
+            .map(|s| (s.start, s.end, s.zero_passes))
+            .collect();
+        assert_eq!(
+            passes,
+            vec![
+                (50, 82, 1),
+                (82, 52, 0),
+                (52, 0, 1),
+                (0, 0, 10),
+                (0, 50, 0),
+                (50, 0, 2),
+                (0, 50, 2)
+            ]
+        );
+        assert_eq!(
+            steps[2].to_string(),
+            "R48    52 ->  0  passes 0 1 time(s), landed on 0"
+        );
+
+        let rotations = super::parse_input(EXAMPLE_2).unwrap();
+        let total: i32 = super::trace(&rotations).iter().map(|s| s.zero_passes).sum();
+        assert_eq!(total, 6);
+    }
+
+    #[test]
+    fn test_dial() {
+        let picture = super::dial(&super::trace(&[-25])[0]);
+        let lines: Vec<&str> = picture.lines().collect();
+        assert_eq!(lines.len(), 12);
+        assert_eq!(lines[0], "L25    50 -> 25  passes 0 0 time(s)");
+        // 0 at the top, 25 on the right and 50 at the bottom
+        assert_eq!(&lines[1][10..11], "0");
+        assert!(lines[6].ends_with('@'));
+        assert_eq!(&lines[11][10..11], "o");
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let input = "L68\nL30\nR4x\n";
+        let err = super::parse_input(input).unwrap_err().locate(input);
+        assert_eq!((err.line, err.column, err.found.as_str()), (3, 2, "4x"));
     }
 }
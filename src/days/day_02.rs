@@ -1,15 +1,14 @@
-use crate::utils::input::read_input;
+use super::DayError;
+use crate::utils::{
+    input::read_input,
+    parse::{pair, ParseError},
+};
 
-fn parse_input(input: &str) -> Vec<(u64, u64)> {
+fn parse_input(input: &str) -> Result<Vec<(u64, u64)>, ParseError> {
     input
         .trim()
         .split(',')
-        .map(|range| {
-            let mut min_max = range.split('-');
-            let min_str = min_max.next().unwrap();
-            let max_str = min_max.next().unwrap();
-            (min_str.parse().unwrap(), max_str.parse().unwrap())
-        })
+        .map(|range| pair(range, '-'))
         .collect()
 }
 
@@ -43,8 +42,8 @@ fn is_invalid_v2(n: u64) -> bool {
     false
 }
 
-fn solve_part_1(input: &str) {
-    let parsed_input = parse_input(input);
+fn solve_part_1(input: &str) -> Result<u64, ParseError> {
+    let parsed_input = parse_input(input)?;
     let mut result = 0;
 
     for (min, max) in parsed_input {
@@ -56,10 +55,12 @@ fn solve_part_1(input: &str) {
     }
 
     println!("Part 1 result: {}", result);
+
+    Ok(result)
 }
 
-fn solve_part_2(input: &str) {
-    let parsed_input = parse_input(input);
+fn solve_part_2(input: &str) -> Result<u64, ParseError> {
+    let parsed_input = parse_input(input)?;
     let mut result = 0;
 
     for (min, max) in parsed_input {
@@ -71,16 +72,22 @@ fn solve_part_2(input: &str) {
     }
 
     println!("Part 2 result: {}", result);
+
+    Ok(result)
 }
 
-pub fn part_1() {
+pub fn part_1() -> Result<String, DayError> {
     let input = read_input(module_path!());
-    solve_part_1(input.as_str());
+    solve_part_1(input.as_str())
+        .map(|answer| answer.to_string())
+        .map_err(|e| e.locate(&input).into())
 }
 
-pub fn part_2() {
+pub fn part_2() -> Result<String, DayError> {
     let input = read_input(module_path!());
-    solve_part_2(input.as_str());
+    solve_part_2(input.as_str())
+        .map(|answer| answer.to_string())
+        .map_err(|e| e.locate(&input).into())
 }
 
 #[cfg(test)]
@@ -93,13 +100,13 @@ mod test {
 
     #[test]
     fn test_part_1() {
-        super::solve_part_1(EXAMPLE_1);
+        super::solve_part_1(EXAMPLE_1).unwrap();
     }
 
     const EXAMPLE_2: &str = EXAMPLE_1;
 
     #[test]
     fn test_part_2() {
-        super::solve_part_2(EXAMPLE_2);
+        super::solve_part_2(EXAMPLE_2).unwrap();
     }
 }
@@ -1,7 +1,11 @@
-use crate::utils::input::read_input;
+use super::DayError;
+use crate::utils::{
+    input::read_input,
+    parse::{char_grid, ParseError},
+};
 
-fn parse_input(input: &str) -> Vec<Vec<char>> {
-    input.lines().map(|line| line.chars().collect()).collect()
+fn parse_input(input: &str) -> Result<Vec<Vec<char>>, ParseError> {
+    char_grid(input, "0123456789")
 }
 
 fn find_max_digit(row: &[char]) -> (usize, char) {
@@ -15,8 +19,8 @@ fn find_max_digit(row: &[char]) -> (usize, char) {
     (pos, *digit)
 }
 
-fn solve_part_1(input: &str) {
-    let rows = parse_input(input);
+fn solve_part_1(input: &str) -> Result<u32, ParseError> {
+    let rows = parse_input(input)?;
     let total: u32 = rows
         .iter()
         .map(|row| {
@@ -32,10 +36,12 @@ fn solve_part_1(input: &str) {
         .sum();
 
     println!("Joltage: {}", total);
+
+    Ok(total)
 }
 
-fn solve_part_2(input: &str) {
-    let rows = parse_input(input);
+fn solve_part_2(input: &str) -> Result<u64, ParseError> {
+    let rows = parse_input(input)?;
     let total: u64 = rows
         .iter()
         .map(|row| {
@@ -52,16 +58,22 @@ fn solve_part_2(input: &str) {
         .sum();
 
     println!("Joltage: {}", total);
+
+    Ok(total)
 }
 
-pub fn part_1() {
+pub fn part_1() -> Result<String, DayError> {
     let input = read_input(module_path!());
-    solve_part_1(input.as_str());
+    solve_part_1(input.as_str())
+        .map(|answer| answer.to_string())
+        .map_err(|e| e.locate(&input).into())
 }
 
-pub fn part_2() {
+pub fn part_2() -> Result<String, DayError> {
     let input = read_input(module_path!());
-    solve_part_2(input.as_str());
+    solve_part_2(input.as_str())
+        .map(|answer| answer.to_string())
+        .map_err(|e| e.locate(&input).into())
 }
 
 #[cfg(test)]
@@ -77,13 +89,13 @@ mod test {
 
     #[test]
     fn test_part_1() {
-        super::solve_part_1(EXAMPLE_1);
+        super::solve_part_1(EXAMPLE_1).unwrap();
     }
 
     const EXAMPLE_2: &str = EXAMPLE_1;
 
     #[test]
     fn test_part_2() {
-        super::solve_part_2(EXAMPLE_2);
+        super::solve_part_2(EXAMPLE_2).unwrap();
     }
 }
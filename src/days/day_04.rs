@@ -1,123 +1,250 @@
-use std::collections::HashSet;
-
-use crate::utils::input::read_input;
-
-fn parse_input(input: &str) -> (HashSet<(isize, isize)>, usize, usize) {
-    let xsize = input.lines().next().unwrap().len();
-    let ysize = input.lines().count();
-    let map = input
-        .lines()
-        .enumerate()
-        .flat_map(|(y, line)| {
-            let set: HashSet<(isize, isize)> = line
-                .char_indices()
-                .filter_map(|(x, c)| match c {
-                    '@' => Some((x as isize, y as isize)),
-                    _ => None,
-                })
-                .collect();
-            set
-        })
-        .collect();
-
-    (map, xsize, ysize)
+use std::{
+    fs,
+    io::{self, Write},
+    thread,
+    time::Duration,
+};
+
+use super::DayError;
+use crate::utils::{
+    grid::{Grid, Pos},
+    input::read_input,
+    options,
+    parse::ParseError,
+    render::{Gif, Image, Rgb},
+};
+
+/// Pixels per cell in rendered frames.
+const SCALE: usize = 4;
+/// Time between rounds when none is given with `--delay`.
+const FRAME_DELAY: Duration = Duration::from_millis(150);
+const LAST_FRAME_DELAY: Duration = Duration::from_secs(2);
+
+fn parse_input(input: &str) -> Result<Grid<char>, ParseError> {
+    Grid::parse(input, ".@")
 }
 
-fn solve_part_1(input: &str) {
-    let mut total = 0;
-    let (map, _xsize, _ysize) = parse_input(input);
-
-    for (x, y) in &map {
-        let mut neighbours = 0;
-        for (dx, dy) in [
-            (-1, -1),
-            (0, -1),
-            (1, -1),
-            (-1, 0),
-            (1, 0),
-            (-1, 1),
-            (0, 1),
-            (1, 1),
-        ] {
-            if map.contains(&(x + dx, y + dy)) {
-                neighbours += 1;
-            }
-        }
-        if neighbours < 4 {
-            total += 1;
-        }
-    }
+fn count_neighbours(map: &Grid<char>, pos: Pos) -> usize {
+    map.neighbours_8(pos)
+        .filter(|n| map.get(*n) == Some(&'@'))
+        .count()
+}
+
+fn solve_part_1(input: &str) -> Result<usize, ParseError> {
+    let map = parse_input(input)?;
+
+    let total = map
+        .positions(|c| *c == '@')
+        .filter(|pos| count_neighbours(&map, *pos) < 4)
+        .count();
 
     println!("Accessible rolls: {}", total);
+
+    Ok(total)
+}
+
+/// The map with the rolls of `to_remove` highlighted, colours being
+/// configurable as `empty`, `roll` and `removed`.
+fn render_round(map: &Grid<char>, to_remove: &[Pos]) -> Image {
+    let opts = options::get();
+    let empty = opts.color("empty", Rgb(0x1e, 0x1e, 0x2e));
+    let roll = opts.color("roll", Rgb(0xc8, 0xa0, 0x50));
+    let removed = opts.color("removed", Rgb(0xe0, 0x40, 0x40));
+
+    let mut image = Image::from_grid(map, SCALE, |c| if *c == '@' { roll } else { empty });
+    for (x, y) in to_remove {
+        image.fill_cell(*x, *y, SCALE, removed);
+    }
+    image
 }
 
-fn display_map(map: &HashSet<(isize, isize)>, xsize: usize, ysize: usize) -> () {
-    for y in 0..ysize {
-        for x in 0..xsize {
-            if map.contains(&(x as isize, y as isize)) {
-                print!("@");
+/// A terminal frame of a round: its number and the rolls removed so far,
+/// over the map with the rolls of `to_remove` in red. It starts by moving the
+/// cursor home so that each frame is drawn over the previous one.
+fn render_ansi(map: &Grid<char>, to_remove: &[Pos], round: usize, removed_cnt: usize) -> String {
+    let mut marked = Grid::new(map.width(), map.height(), false);
+    for pos in to_remove {
+        marked.set(*pos, true);
+    }
+
+    let mut frame = format!(
+        "\x1b[HRound {}: {} removed, {} to remove\x1b[K\n",
+        round,
+        removed_cnt,
+        to_remove.len()
+    );
+    for (y, row) in map.rows().enumerate() {
+        for (x, c) in row.iter().enumerate() {
+            if marked.get((x, y)) == Some(&true) {
+                frame.push_str("\x1b[31m@\x1b[m");
             } else {
-                print!(".");
+                frame.push(*c);
             }
         }
-        println!("")
+        frame.push('\n');
     }
-    println!("")
+    frame
 }
 
-fn solve_part_2(input: &str) {
-    let mut removed_cnt = 0;
-    let (mut map, xsize, ysize) = parse_input(input);
-
-    display_map(&map, xsize, ysize);
-
-    loop {
-        let mut to_remove: Vec<(isize, isize)> = Vec::new();
-
-        for (x, y) in &map {
-            let mut neighbours = 0;
-            for (dx, dy) in [
-                (-1, -1),
-                (0, -1),
-                (1, -1),
-                (-1, 0),
-                (1, 0),
-                (-1, 1),
-                (0, 1),
-                (1, 1),
-            ] {
-                if map.contains(&(x + dx, y + dy)) {
-                    neighbours += 1;
-                }
-            }
-            if neighbours < 4 {
-                to_remove.push((*x, *y));
+/// Terminal colours from blue through green and yellow to red, for the
+/// rounds of the heatmap.
+const HEAT: [u8; 21] = [
+    21, 27, 33, 39, 45, 51, 50, 49, 48, 47, 46, 82, 118, 154, 190, 226, 220, 214, 208, 202, 196,
+];
+
+/// The colour of round `round` of `rounds`, from the first of [`HEAT`] to the
+/// last.
+fn heat(round: usize, rounds: usize) -> u8 {
+    HEAT[(round - 1) * (HEAT.len() - 1) / (rounds - 1).max(1)]
+}
+
+/// The last round a roll was removed in, 0 if none was.
+fn max_round(generations: &Grid<Option<usize>>) -> usize {
+    generations
+        .iter()
+        .filter_map(|(_, g)| *g)
+        .max()
+        .unwrap_or(0)
+}
+
+/// The rolls of the starting map coloured by the round they were removed in,
+/// from `generations`, the rolls left in `map` being in bold.
+fn heatmap_ansi(map: &Grid<char>, generations: &Grid<Option<usize>>) -> String {
+    let rounds = max_round(generations);
+    let mut view = String::new();
+    for (y, row) in map.rows().enumerate() {
+        for (x, c) in row.iter().enumerate() {
+            match generations.get((x, y)).unwrap() {
+                Some(g) => view.push_str(&format!("\x1b[38;5;{}m@\x1b[m", heat(*g, rounds))),
+                None if *c == '@' => view.push_str("\x1b[1m@\x1b[m"),
+                None => view.push('.'),
             }
         }
-        removed_cnt += to_remove.len();
+        view.push('\n');
+    }
+    if rounds > 0 {
+        view.push_str(&format!(
+            "Removed in round \x1b[38;5;{}m1\x1b[m to \x1b[38;5;{}m{}\x1b[m, never removed in \x1b[1mbold\x1b[m\n",
+            heat(1, rounds),
+            heat(rounds, rounds),
+            rounds
+        ));
+    }
+    view
+}
 
-        if to_remove.len() == 0 {
-            // finished
+/// Same as [`heatmap_ansi`] as an image, the rolls left being of the `roll`
+/// colour.
+fn heatmap_image(map: &Grid<char>, generations: &Grid<Option<usize>>) -> Image {
+    let opts = options::get();
+    let empty = opts.color("empty", Rgb(0x1e, 0x1e, 0x2e));
+    let roll = opts.color("roll", Rgb(0xc8, 0xa0, 0x50));
+    let rounds = max_round(generations);
+
+    let cells = generations.map(|g| g.map(|g| Rgb::from_ansi256(heat(g, rounds))));
+    let mut image = Image::from_grid(&cells, SCALE, |c| c.unwrap_or(empty));
+    for (x, y) in map.positions(|c| *c == '@') {
+        image.fill_cell(x, y, SCALE, roll);
+    }
+    image
+}
+
+/// The round each roll of `map` is removed in, `None` for the rolls that
+/// stay. Each round removes at once every roll with fewer than 4 neighbours.
+fn removal_rounds(map: &Grid<char>) -> Grid<Option<usize>> {
+    let mut map = map.clone();
+    let mut generations = Grid::new(map.width(), map.height(), None);
+    for round in 1.. {
+        let to_remove: Vec<Pos> = map
+            .positions(|c| *c == '@')
+            .filter(|pos| count_neighbours(&map, *pos) < 4)
+            .collect();
+        if to_remove.is_empty() {
             break;
         }
+        for pos in to_remove {
+            map.set(pos, '.');
+            generations.set(pos, Some(round));
+        }
+    }
+    generations
+}
+
+fn solve_part_2(input: &str) -> Result<usize, ParseError> {
+    let mut removed_cnt = 0;
+    let mut map = parse_input(input)?;
+    let generations = removal_rounds(&map);
+    let opts = options::get();
+    let delay = opts.delay.unwrap_or(FRAME_DELAY);
+    let mut gif = Gif::new(map.width() * SCALE, map.height() * SCALE);
+
+    if opts.animate {
+        // clear the screen once, frames then overwrite each other
+        print!("\x1b[2J");
+    }
+
+    // replay the rounds, the one after the last removing nothing
+    for round in 1..=max_round(&generations) + 1 {
+        let to_remove: Vec<Pos> = generations.positions(move |g| *g == Some(round)).collect();
+
+        if opts.animate {
+            print!("{}", render_ansi(&map, &to_remove, round, removed_cnt));
+            io::stdout().flush().unwrap();
+            thread::sleep(delay);
+        }
+        if opts.render.is_some() {
+            let delay = if to_remove.is_empty() {
+                LAST_FRAME_DELAY
+            } else {
+                delay
+            };
+            gif.push_frame(&render_round(&map, &to_remove), delay);
+        }
 
-        for coord in &to_remove {
-            map.remove(&coord);
+        removed_cnt += to_remove.len();
+        for pos in &to_remove {
+            map.set(*pos, '.');
         }
-        display_map(&map, xsize, ysize);
     }
 
     println!("Removed rolls: {}", removed_cnt);
+
+    if opts.heatmap {
+        print!("{}", heatmap_ansi(&map, &generations));
+    }
+    if let Some(path) = opts.heatmap_png.as_ref() {
+        match fs::write(path, heatmap_image(&map, &generations).to_png()) {
+            Ok(()) => println!("Heatmap drawn to {}", path.display()),
+            Err(e) => eprintln!("\x1b[31merror:\x1b[m {}: {}", path.display(), e),
+        }
+    }
+
+    if let Some(path) = opts.render.as_ref() {
+        match fs::write(path, gif.encode()) {
+            Ok(()) => println!(
+                "{} rounds rendered to {}",
+                gif.frame_count(),
+                path.display()
+            ),
+            Err(e) => eprintln!("\x1b[31merror:\x1b[m {}: {}", path.display(), e),
+        }
+    }
+
+    Ok(removed_cnt)
 }
 
-pub fn part_1() {
+pub fn part_1() -> Result<String, DayError> {
     let input = read_input(module_path!());
-    solve_part_1(input.as_str());
+    solve_part_1(input.as_str())
+        .map(|answer| answer.to_string())
+        .map_err(|e| e.locate(&input).into())
 }
 
-pub fn part_2() {
+pub fn part_2() -> Result<String, DayError> {
     let input = read_input(module_path!());
-    solve_part_2(input.as_str());
+    solve_part_2(input.as_str())
+        .map(|answer| answer.to_string())
+        .map_err(|e| e.locate(&input).into())
 }
 
 #[cfg(test)]
@@ -139,13 +266,103 @@ mod test {
 
     #[test]
     fn test_part_1() {
-        super::solve_part_1(EXAMPLE_1);
+        super::solve_part_1(EXAMPLE_1).unwrap();
     }
 
     const EXAMPLE_2: &str = EXAMPLE_1;
 
     #[test]
     fn test_part_2() {
-        super::solve_part_2(EXAMPLE_2);
+        super::solve_part_2(EXAMPLE_2).unwrap();
+    }
+
+    #[test]
+    fn test_render_ansi() {
+        let map = super::parse_input("@@.\n.@@\n").unwrap();
+        let frame = super::render_ansi(&map, &[(0, 0), (2, 1)], 3, 7);
+        assert_eq!(
+            frame,
+            "\x1b[HRound 3: 7 removed, 2 to remove\x1b[K\n\x1b[31m@\x1b[m@.\n.@\x1b[31m@\x1b[m\n"
+        );
+    }
+
+    /// `input` once every round is over, each roll removed shown as the
+    /// round it was removed in.
+    fn removal_view(input: &str) -> String {
+        let map = super::parse_input(input).unwrap();
+        let generations = super::removal_rounds(&map);
+        let mut view = String::new();
+        for (row, rounds) in map.rows().zip(generations.rows()) {
+            for (c, round) in row.iter().zip(rounds) {
+                view.push(round.map_or(*c, |r| char::from_digit(r as u32, 10).unwrap()));
+            }
+            view.push('\n');
+        }
+        view
+    }
+
+    #[test]
+    fn test_removal_rounds() {
+        // the corners go first, then the edges, then the middle
+        assert_eq!(removal_view("@@@\n@@@\n@@@\n"), "121\n232\n121\n");
+        // only the corners of a bigger block go
+        assert_eq!(
+            removal_view("@@@@\n@@@@\n@@@@\n@@@@\n"),
+            "1@@1\n@@@@\n@@@@\n1@@1\n"
+        );
+
+        let expected = indoc! {"
+            ..11.1121.
+            134.2.2.32
+            24578.1.33
+            2.69@@..2.
+            13.@@@@.21
+            .24@@@@@.2
+            .2.@.@.@@3
+            1.4@@.@@@4
+            .23@@@@@5.
+            1.1.@@@.1.
+        "};
+        assert_eq!(removal_view(EXAMPLE_1), expected);
+    }
+
+    #[test]
+    fn test_heatmap() {
+        let mut map = super::parse_input("@@@\n@@@\n@@@\n").unwrap();
+        let generations = super::removal_rounds(&map);
+        for (x, y) in generations.positions(|g| g.is_some()) {
+            map.set((x, y), '.');
+        }
+
+        let (first, second, last) = (
+            "\x1b[38;5;21m@\x1b[m",
+            "\x1b[38;5;46m@\x1b[m",
+            "\x1b[38;5;196m@\x1b[m",
+        );
+        let view = super::heatmap_ansi(&map, &generations);
+        let lines: Vec<&str> = view.lines().collect();
+        assert_eq!(lines[0], [first, second, first].concat());
+        assert_eq!(lines[1], [second, last, second].concat());
+        assert!(lines[3].starts_with("Removed in round"));
+
+        let image = super::heatmap_image(&map, &generations);
+        assert_eq!(image.get(0, 0), Some(super::Rgb(0, 0, 0xff)));
+        assert_eq!(image.get(4, 4), Some(super::Rgb(0xff, 0, 0)));
+        assert_ne!(image.get(4, 0), image.get(0, 0));
+
+        // the rolls that stay are in bold
+        let map = super::parse_input("@@@@\n@@@@\n@@@@\n@@@@\n").unwrap();
+        let view = super::heatmap_ansi(&map, &super::removal_rounds(&map));
+        assert!(view.lines().nth(1).unwrap().starts_with("\x1b[1m@\x1b[m"));
+    }
+
+    #[test]
+    fn test_render_round() {
+        let map = super::parse_input(EXAMPLE_1).unwrap();
+        let image = super::render_round(&map, &[(2, 0)]);
+        assert_eq!((image.width(), image.height()), (40, 40));
+        let (removed, roll) = (image.get(9, 1).unwrap(), image.get(13, 1).unwrap());
+        assert_ne!(removed, roll);
+        assert_eq!(image.get(8, 0), image.get(11, 3));
     }
 }
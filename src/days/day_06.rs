@@ -1,34 +1,59 @@
-use std::ops::Range;
+use std::{fmt::Write, ops::Range};
 
-use crate::utils::input::read_input;
+use super::DayError;
+use crate::utils::{
+    input::{read_input_with, Normalize},
+    options,
+    parse::{words, ParseError},
+};
+
+/// Column positions matter, so trailing spaces must stay.
+const NORMALIZE: Normalize = Normalize {
+    trailing_spaces: false,
+    ..Normalize::ALL
+};
 
 type Operand = i64;
+type Column = (Range<usize>, char);
+
+/// Splits the worksheet into its number rows and its operator row.
+fn split_rows(input: &str) -> Result<(Vec<&str>, &str), ParseError> {
+    let mut lines: Vec<&str> = input.lines().collect();
+    let ops_line = lines.pop().ok_or(ParseError::new(
+        input,
+        "rows of numbers and a row of operators",
+    ))?;
 
-fn parse_input(input: &str) -> Vec<(Vec<Operand>, char)> {
-    let nrows = input.lines().count() - 1;
-    let ncols = input.lines().next().unwrap().split_whitespace().count();
+    if let Some((i, c)) = ops_line.char_indices().find(|(_, c)| !"+* ".contains(*c)) {
+        let found = &ops_line[i..i + c.len_utf8()];
+        return Err(ParseError::new(found, "'+', '*' or ' '"));
+    }
+
+    Ok((lines, ops_line))
+}
 
-    let mut cols: Vec<Vec<Operand>> = vec![vec![0; nrows]; ncols];
+fn parse_input(input: &str) -> Result<Vec<(Vec<Operand>, char)>, ParseError> {
+    let (lines, ops_line) = split_rows(input)?;
+    let ops: Vec<char> = ops_line.chars().filter(|c| *c != ' ').collect();
+    let ncols = ops.len();
 
-    for (ln, line) in input.lines().take(nrows).enumerate() {
-        for (wn, word) in line.split_whitespace().enumerate() {
-            cols[wn][ln] = word.parse::<Operand>().unwrap();
+    let mut cols: Vec<Vec<Operand>> = vec![vec![0; lines.len()]; ncols];
+
+    for (ln, line) in lines.iter().enumerate() {
+        let operands: Vec<Operand> = words(line)?;
+        if operands.len() != ncols {
+            return Err(ParseError::new(line, format!("{} numbers", ncols)));
+        }
+        for (wn, operand) in operands.into_iter().enumerate() {
+            cols[wn][ln] = operand;
         }
     }
 
-    let ops: Vec<char> = input
-        .lines()
-        .last()
-        .unwrap()
-        .split_whitespace()
-        .map(|s| s.chars().next().unwrap())
-        .collect();
-
-    cols.into_iter().zip(ops).collect()
+    Ok(cols.into_iter().zip(ops).collect())
 }
 
-fn solve_part_1(input: &str) {
-    let ops = parse_input(input);
+fn solve_part_1(input: &str) -> Result<Operand, ParseError> {
+    let ops = parse_input(input)?;
     // println!("{:?}", bla);
 
     let sum: Operand = ops
@@ -43,54 +68,116 @@ fn solve_part_1(input: &str) {
         .sum();
 
     println!("Grand total: {}", sum);
+
+    Ok(sum)
 }
 
-fn parse_input_v2(input: &str) -> (Vec<String>, Vec<(Range<usize>, char)>) {
-    let nrows = input.lines().count() - 1;
-    let ncols = input.lines().next().unwrap().split_whitespace().count();
+fn parse_input_v2(input: &str) -> Result<(Vec<&str>, Vec<Column>), ParseError> {
+    let (lines, ops_line) = split_rows(input)?;
 
-    let mut positions: Vec<(usize, char)> = input
-        .lines()
-        .last()
-        .unwrap()
+    for line in lines.iter() {
+        if let Some((i, c)) = line
+            .char_indices()
+            .find(|(_, c)| !c.is_ascii_digit() && *c != ' ')
+        {
+            return Err(ParseError::new(
+                &line[i..i + c.len_utf8()],
+                "a digit or ' '",
+            ));
+        }
+    }
+
+    let mut positions: Vec<(usize, char)> = ops_line
         .chars()
         .enumerate()
         .filter(|(_, c)| *c != ' ')
         .collect();
-    positions.push((input.lines().next().unwrap().len() + 1, ' '));
+    let ncols = positions.len();
+    // the last column ends with the longest line, in case lines are ragged
+    let width = lines.iter().map(|line| line.len()).max().unwrap_or(0);
+    positions.push((width + 1, ' '));
 
-    let mut cols: Vec<(Range<usize>, char)> = Vec::new();
+    let mut cols: Vec<Column> = Vec::new();
     for nc in 0..ncols {
         cols.push((positions[nc].0..positions[nc + 1].0 - 1, positions[nc].1));
     }
 
-    let lines: Vec<String> = input
-        .lines()
-        .take(nrows)
-        .map(|line| line.to_owned())
-        .collect();
+    Ok((lines, cols))
+}
+
+/// The numbers of a problem read top to bottom, one per character column.
+fn column_numbers(lines: &[&str], range: Range<usize>) -> Vec<Operand> {
+    range
+        .map(|pos| {
+            let mut acc: Operand = 0;
+            for line in lines.iter() {
+                match line.chars().nth(pos) {
+                    Some(' ') => (),
+                    Some(c) => acc = 10 * acc + (c as Operand - '0' as Operand),
+                    _ => (),
+                }
+            }
+            acc
+        })
+        .collect()
+}
+
+/// The worksheet with the columns found by [`parse_input_v2`] in brackets,
+/// then the numbers each part reads for each problem.
+fn render_columns(input: &str) -> Result<String, ParseError> {
+    let (lines, cols) = parse_input_v2(input)?;
+    let (_, ops_line) = split_rows(input)?;
+    // part 1 reads by words, which may fail where part 2 doesn't
+    let rows = parse_input(input).ok();
 
-    (lines, cols)
+    let mut view = String::new();
+    for line in lines.iter().chain([&ops_line]) {
+        let mut end = 0;
+        for (range, _) in cols.iter() {
+            // the separator before the column, then the column itself
+            view.push_str(line.get(end..range.start).unwrap_or(""));
+            let cells: String = (range.clone())
+                .map(|pos| line.chars().nth(pos).unwrap_or(' '))
+                .collect();
+            write!(view, "[{}]", cells).unwrap();
+            end = range.end;
+        }
+        // anything past the last column
+        view.push_str(line.get(end..).unwrap_or(""));
+        view.push('\n');
+    }
+
+    for (i, (range, op)) in cols.iter().enumerate() {
+        let join = |numbers: &[Operand]| {
+            let numbers: Vec<String> = numbers.iter().map(|n| n.to_string()).collect();
+            numbers.join(&format!(" {} ", op))
+        };
+        let by_rows = match rows.as_ref().and_then(|rows| rows.get(i)) {
+            Some((numbers, _)) => join(numbers),
+            None => String::from("-"),
+        };
+        let by_columns = join(&column_numbers(&lines, range.clone()));
+        writeln!(
+            view,
+            "{:>3}. {:?}  part 1: {}  part 2: {}",
+            i + 1,
+            range,
+            by_rows,
+            by_columns
+        )
+        .unwrap();
+    }
+
+    Ok(view)
 }
 
-fn solve_part_2(input: &str) {
-    let (lines, cols) = parse_input_v2(input);
-    // println!("{:?}", cols);
+fn solve_part_2(input: &str) -> Result<Operand, ParseError> {
+    let (lines, cols) = parse_input_v2(input)?;
 
     let total: Operand = cols
         .into_iter()
         .map(|(ran, op)| {
-            let numbers = ran.map(|pos| {
-                let mut acc: Operand = 0;
-                for ln in 0..lines.len() {
-                    match lines[ln].chars().nth(pos) {
-                        Some(' ') => (),
-                        Some(c) => acc = 10 * acc + (c as Operand - '0' as Operand),
-                        _ => (),
-                    }
-                }
-                acc
-            });
+            let numbers = column_numbers(&lines, ran).into_iter();
             if op == '+' {
                 numbers.sum::<Operand>()
             } else {
@@ -100,16 +187,26 @@ fn solve_part_2(input: &str) {
         .sum();
 
     println!("Grand total: {}", total);
+
+    if options::get().columns {
+        print!("{}", render_columns(input)?);
+    }
+
+    Ok(total)
 }
 
-pub fn part_1() {
-    let input = read_input(module_path!());
-    solve_part_1(input.as_str());
+pub fn part_1() -> Result<String, DayError> {
+    let input = read_input_with(module_path!(), &NORMALIZE);
+    solve_part_1(input.as_str())
+        .map(|answer| answer.to_string())
+        .map_err(|e| e.locate(&input).into())
 }
 
-pub fn part_2() {
-    let input = read_input(module_path!());
-    solve_part_2(input.as_str());
+pub fn part_2() -> Result<String, DayError> {
+    let input = read_input_with(module_path!(), &NORMALIZE);
+    solve_part_2(input.as_str())
+        .map(|answer| answer.to_string())
+        .map_err(|e| e.locate(&input).into())
 }
 
 #[cfg(test)]
@@ -125,13 +222,42 @@ mod test {
 
     #[test]
     fn test_part_1() {
-        super::solve_part_1(EXAMPLE_1);
+        super::solve_part_1(EXAMPLE_1).unwrap();
     }
 
     const EXAMPLE_2: &str = EXAMPLE_1;
 
     #[test]
     fn test_part_2() {
-        super::solve_part_2(EXAMPLE_2);
+        super::solve_part_2(EXAMPLE_2).unwrap();
+    }
+
+    #[test]
+    fn test_render_columns() {
+        let view = super::render_columns(EXAMPLE_1).unwrap();
+        let expected = indoc! {"
+            [123] [328] [ 51] [64 ]
+            [ 45] [64 ] [387] [23 ]
+            [  6] [98 ] [215] [314]
+            [*  ] [+  ] [*  ] [+  ]
+              1. 0..3  part 1: 123 * 45 * 6  part 2: 1 * 24 * 356
+              2. 4..7  part 1: 328 + 64 + 98  part 2: 369 + 248 + 8
+              3. 8..11  part 1: 51 * 387 * 215  part 2: 32 * 581 * 175
+              4. 12..15  part 1: 64 + 23 + 314  part 2: 623 + 431 + 4
+        "};
+        assert_eq!(view, expected);
+    }
+
+    #[test]
+    fn test_render_ragged() {
+        // a shorter first line still gets the whole last column
+        let view = super::render_columns("12 3\n45 67\n*  + \n").unwrap();
+        let lines: Vec<&str> = view.lines().collect();
+        assert_eq!(lines[..3], ["[12] [3 ]", "[45] [67]", "[* ] [+ ]"]);
+        assert!(lines[4].ends_with("part 1: 3 + 67  part 2: 36 + 7"));
+        assert_eq!(
+            super::solve_part_2("12 3\n45 67\n*  + \n"),
+            Ok(14 * 25 + 43)
+        );
     }
 }
@@ -1,78 +1,131 @@
-use std::{
-    collections::{HashMap, HashSet},
-    usize,
+use std::fmt::Write;
+
+use super::DayError;
+use crate::utils::{
+    grid::{Grid, Pos},
+    input::read_input,
+    options,
+    parse::ParseError,
 };
 
-use crate::utils::input::read_input;
-
-fn parse_input(input: &str) -> (Vec<HashSet<usize>>, usize) {
-    let start_pos = input.find('S').unwrap();
-    let splitters = input
-        .lines()
-        .skip(1)
-        .map(|l| {
-            l.char_indices()
-                .filter_map(|(pos, char)| if char == '^' { Some(pos) } else { None })
-                .collect()
-        })
-        .collect();
-
-    (splitters, start_pos)
+fn parse_input(input: &str) -> Result<(Grid<char>, Pos), ParseError> {
+    let manifold = Grid::parse(input, ".S^|")?;
+    let start_pos = manifold
+        .row(0)
+        .iter()
+        .position(|c| *c == 'S')
+        .ok_or_else(|| {
+            let first_line = input.lines().next().unwrap_or(input);
+            ParseError::new(first_line, "a start position 'S' on the first line")
+        })?;
+
+    Ok((manifold, (start_pos, 0)))
 }
 
-fn solve_part_1(input: &str) {
-    let (splitters, start_pos) = parse_input(input);
-    let mut booms: usize = 0;
-    let mut beams: HashSet<usize> = HashSet::new();
-    beams.insert(start_pos);
-
-    for s in splitters {
-        let intersect: HashSet<usize> = beams.intersection(&s).cloned().collect();
-        booms += intersect.len();
-        for i in intersect {
-            beams.remove(&i);
-            beams.insert(i - 1);
-            beams.insert(i + 1);
+fn is_splitter(row: &[char], x: usize) -> bool {
+    row.get(x) == Some(&'^')
+}
+
+/// The number of timelines going through each cell, row by row. Beams split
+/// off the sides of the manifold are lost.
+fn trace_timelines(manifold: &Grid<char>, start_x: usize) -> Vec<Vec<u64>> {
+    let mut counts = vec![vec![0; manifold.width()]; manifold.height()];
+    counts[0][start_x] = 1;
+
+    for (y, row) in manifold.rows().enumerate().skip(1) {
+        for x in 0..row.len() {
+            let n = counts[y - 1][x];
+            if n == 0 {
+                continue;
+            }
+            if is_splitter(row, x) {
+                if x > 0 {
+                    counts[y][x - 1] += n;
+                }
+                if x + 1 < row.len() {
+                    counts[y][x + 1] += n;
+                }
+            } else {
+                counts[y][x] += n;
+            }
         }
     }
 
-    println!("Booms: {:?}", booms);
+    counts
 }
 
-fn solve_part_2(input: &str) {
-    let (splitters, start_pos) = parse_input(input);
-    let mut beams: HashMap<usize, u64> = HashMap::new();
-    beams.insert(start_pos, 1);
-
-    for s in splitters {
-        let intersect: HashSet<usize> = beams
-            .keys()
-            .cloned()
-            .collect::<HashSet<usize>>()
-            .intersection(&s)
-            .cloned()
-            .collect();
-
-        for i in intersect {
-            let n = beams.remove(&i).unwrap();
-            beams.entry(i - 1).and_modify(|v| *v += n).or_insert(n);
-            beams.entry(i + 1).and_modify(|v| *v += n).or_insert(n);
+/// Whether a beam reaches the cell at `(x, y)`, from the timeline `counts`.
+fn is_reached(counts: &[Vec<u64>], (x, y): Pos) -> bool {
+    y > 0 && counts[y - 1][x] > 0
+}
+
+/// The manifold with its beams drawn in and the splitters they hit in red,
+/// followed on each row by the timelines going through each column if
+/// `with_counts` is set.
+fn render_beams(manifold: &Grid<char>, start_x: usize, with_counts: bool) -> String {
+    let counts = trace_timelines(manifold, start_x);
+    let mut view = String::new();
+
+    for (y, row) in manifold.rows().enumerate() {
+        for (x, c) in row.iter().enumerate() {
+            match c {
+                '^' if is_reached(&counts, (x, y)) => view.push_str("\x1b[1;31m^\x1b[m"),
+                '^' | 'S' => view.push(*c),
+                _ if counts[y][x] > 0 => view.push('|'),
+                _ => view.push('.'),
+            }
+        }
+        if with_counts {
+            for (x, n) in counts[y].iter().enumerate().filter(|(_, n)| **n > 0) {
+                write!(view, " {}:{}", x, n).unwrap();
+            }
         }
+        view.push('\n');
     }
 
-    let timelines = beams.into_values().sum::<u64>();
+    view
+}
+
+fn solve_part_1(input: &str) -> Result<usize, ParseError> {
+    let (manifold, (start_x, _)) = parse_input(input)?;
+    let counts = trace_timelines(&manifold, start_x);
+    let booms = manifold
+        .positions(|c| *c == '^')
+        .filter(|pos| is_reached(&counts, *pos))
+        .count();
+
+    println!("Booms: {:?}", booms);
+
+    Ok(booms)
+}
+
+fn solve_part_2(input: &str) -> Result<u64, ParseError> {
+    let (manifold, (start_x, _)) = parse_input(input)?;
+    let counts = trace_timelines(&manifold, start_x);
+    let timelines = counts.last().map_or(0, |row| row.iter().sum::<u64>());
 
     println!("Timelines: {:?}", timelines);
+
+    let opts = options::get();
+    if opts.beams || opts.beam_counts {
+        print!("{}", render_beams(&manifold, start_x, opts.beam_counts));
+    }
+
+    Ok(timelines)
 }
 
-pub fn part_1() {
+pub fn part_1() -> Result<String, DayError> {
     let input = read_input(module_path!());
-    solve_part_1(input.as_str());
+    solve_part_1(input.as_str())
+        .map(|answer| answer.to_string())
+        .map_err(|e| e.locate(&input).into())
 }
 
-pub fn part_2() {
+pub fn part_2() -> Result<String, DayError> {
     let input = read_input(module_path!());
-    solve_part_2(input.as_str());
+    solve_part_2(input.as_str())
+        .map(|answer| answer.to_string())
+        .map_err(|e| e.locate(&input).into())
 }
 
 #[cfg(test)]
@@ -100,13 +153,55 @@ mod test {
 
     #[test]
     fn test_part_1() {
-        super::solve_part_1(EXAMPLE_1);
+        super::solve_part_1(EXAMPLE_1).unwrap();
     }
 
     const EXAMPLE_2: &str = EXAMPLE_1;
 
     #[test]
     fn test_part_2() {
-        super::solve_part_2(EXAMPLE_2);
+        super::solve_part_2(EXAMPLE_2).unwrap();
+    }
+
+    #[test]
+    fn test_render_beams() {
+        let (manifold, (start_x, _)) = super::parse_input(EXAMPLE_1).unwrap();
+        let view = super::render_beams(&manifold, start_x, true);
+        assert_eq!(view.matches("\x1b[1;31m^").count(), 21);
+
+        let plain = view.replace("\x1b[1;31m", "").replace("\x1b[m", "");
+        let lines: Vec<&str> = plain.lines().collect();
+        assert_eq!(lines[0], ".......S....... 7:1");
+        assert_eq!(lines[2], "......|^|...... 6:1 8:1");
+        assert_eq!(lines[4], ".....|^|^|..... 5:1 7:2 9:1");
+        assert_eq!(
+            lines[15],
+            "|.|.|.|.|.|||.| 0:1 2:2 4:10 6:11 8:11 10:2 11:1 12:1 14:1"
+        );
+    }
+
+    #[test]
+    fn test_answers() {
+        assert_eq!(super::solve_part_1(EXAMPLE_1), Ok(21));
+        assert_eq!(super::solve_part_2(EXAMPLE_2), Ok(40));
+    }
+
+    #[test]
+    fn test_splitters_on_edges() {
+        // the beams split off the sides are lost
+        let first_column = "S..\n^..\n.^.\n...\n";
+        assert_eq!(super::solve_part_1(first_column), Ok(2));
+        assert_eq!(super::solve_part_2(first_column), Ok(2));
+        let last_column = "..S\n..^\n.^.\n...\n";
+        assert_eq!(super::solve_part_1(last_column), Ok(2));
+        assert_eq!(super::solve_part_2(last_column), Ok(2));
+    }
+
+    #[test]
+    fn test_splitter_on_edge() {
+        let input = "S.\n^.\n..\n";
+        let (manifold, (start_x, _)) = super::parse_input(input).unwrap();
+        let view = super::render_beams(&manifold, start_x, false);
+        assert_eq!(view, "S.\n\x1b[1;31m^\x1b[m|\n.|\n");
     }
 }
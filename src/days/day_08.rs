@@ -1,158 +1,187 @@
-use crate::utils::input::read_input;
-
-type JBox = (i64, i64, i64);
-
-fn parse_input(input: &str) -> Vec<JBox> {
-    input
-        .lines()
-        .map(|line| {
-            let mut coords = line.split(',');
-            let x = coords.next().unwrap().parse().unwrap();
-            let y = coords.next().unwrap().parse().unwrap();
-            let z = coords.next().unwrap().parse().unwrap();
-            (x, y, z)
-        })
-        .collect()
+use std::{fmt::Write, fs, path::Path};
+
+use super::DayError;
+use crate::utils::{
+    dsu::UnionFind, geom::Point3, input::read_input, kdtree::KdTree, options, parse::ParseError,
+    render::Rgb,
+};
+
+type JBox = Point3<i64>;
+/// Two boxes by index, and the square of the distance between them.
+type Connection = (usize, usize, i64);
+
+fn parse_input(input: &str) -> Result<Vec<JBox>, ParseError> {
+    input.lines().map(|line| line.parse()).collect()
 }
 
-fn get_distance_squared(b1: &JBox, b2: &JBox) -> i64 {
-    (b2.0 - b1.0).pow(2) + (b2.1 - b1.1).pow(2) + (b2.2 - b1.2).pow(2)
+fn box_tree(boxes: &[JBox]) -> KdTree<3> {
+    let coords: Vec<[i64; 3]> = boxes.iter().map(|&b| b.into()).collect();
+    KdTree::new(&coords)
 }
 
-fn get_box_circuit(circuits: &Vec<Vec<usize>>, box_id: usize) -> Option<usize> {
-    for ci in 0..circuits.len() {
-        if circuits[ci].contains(&box_id) {
-            return Some(ci);
+/// The colour of each box: circuits get hues a golden angle apart, and
+/// boxes left on their own are grey.
+fn box_colors(circuits: &UnionFind) -> Vec<Rgb> {
+    let mut colors = vec![Rgb(0xa0, 0xa0, 0xa0); circuits.len()];
+    let circuits = circuits.components();
+    for (id, circuit) in circuits.iter().filter(|c| c.len() > 1).enumerate() {
+        let hue = (id as f64 * 0.618_034).fract() * 6.0;
+        let f = hue.fract();
+        // HSV to RGB, at saturation 0.6 and value 0.9
+        let (v, p) = (0.9, 0.9 * 0.4);
+        let (q, t) = (0.9 * (1.0 - 0.6 * f), 0.9 * (1.0 - 0.6 * (1.0 - f)));
+        let (r, g, b) = match hue as u8 {
+            0 => (v, t, p),
+            1 => (q, v, p),
+            2 => (p, v, t),
+            3 => (p, q, v),
+            4 => (t, p, v),
+            _ => (v, p, q),
+        };
+        let channel = |c: f64| (c * 255.0).round() as u8;
+        for &bid in circuit {
+            colors[bid] = Rgb(channel(r), channel(g), channel(b));
         }
     }
-    None
+    colors
 }
 
-fn solve_part_1(input: &str, connections: usize) {
-    let boxes = parse_input(input);
-    let mut pairs: Vec<(usize, usize, i64)> = Vec::new();
-    for b1 in 0..boxes.len() - 1 {
-        for b2 in b1 + 1..boxes.len() {
-            pairs.push((b1, b2, get_distance_squared(&boxes[b1], &boxes[b2])));
-        }
+/// A Graphviz graph of the boxes, filled with the colour of their circuit,
+/// and of the connections made, labelled with their length.
+fn circuits_dot(boxes: &[JBox], made: &[Connection], circuits: &UnionFind) -> String {
+    let colors = box_colors(circuits);
+    let mut dot = String::from("graph circuits {\n    node [style=filled];\n");
+    for (bid, b) in boxes.iter().enumerate() {
+        writeln!(
+            dot,
+            "    {} [label=\"{}\\n{}\" fillcolor=\"{}\"];",
+            bid, bid, b, colors[bid]
+        )
+        .unwrap();
     }
-
-    pairs.sort_by(|p1, p2| p1.2.cmp(&p2.2));
-    // println!("{:?}", &pairs[..15]);
-
-    let mut circuits: Vec<Vec<usize>> = Vec::new();
-    let mut connections = connections;
-    let mut pair_id = 0;
-
-    while connections > 0 {
-        loop {
-            let (bid1, bid2, _) = pairs[pair_id];
-            pair_id += 1;
-            let c1 = get_box_circuit(&circuits, bid1);
-            let c2 = get_box_circuit(&circuits, bid2);
-            if let Some(cid1) = c1 {
-                if let Some(cid2) = c2 {
-                    if cid1 == cid2 {
-                        break;
-                    }
-
-                    let mut new = Vec::new();
-                    new.append(&mut circuits[cid1]);
-                    new.append(&mut circuits[cid2]);
-                    circuits[cid1] = new;
-                    circuits.remove(cid2);
-                    break;
-                } else {
-                    circuits[cid1].push(bid2);
-                    break;
-                }
-            } else if let Some(cid2) = c2 {
-                circuits[cid2].push(bid1);
-                break;
-            } else {
-                circuits.push(vec![bid1, bid2]);
-                break;
-            }
-        }
-        connections -= 1;
+    for &(bid1, bid2, dist_sq) in made {
+        writeln!(
+            dot,
+            "    {} -- {} [label=\"{:.1}\"];",
+            bid1,
+            bid2,
+            (dist_sq as f64).sqrt()
+        )
+        .unwrap();
     }
+    dot.push_str("}\n");
+    dot
+}
 
-    circuits.sort_by(|c1, c2| c2.len().cmp(&c1.len()));
+/// A PLY point cloud of the boxes, coloured by circuit, with the connections
+/// made as edges.
+fn circuits_ply(boxes: &[JBox], made: &[Connection], circuits: &UnionFind) -> String {
+    let colors = box_colors(circuits);
+    let mut ply = String::from("ply\nformat ascii 1.0\n");
+    writeln!(ply, "element vertex {}", boxes.len()).unwrap();
+    ply.push_str("property int x\nproperty int y\nproperty int z\n");
+    ply.push_str("property uchar red\nproperty uchar green\nproperty uchar blue\n");
+    writeln!(ply, "element edge {}", made.len()).unwrap();
+    ply.push_str("property int vertex1\nproperty int vertex2\nend_header\n");
 
-    let product = circuits[0].len() * circuits[1].len() * circuits[2].len();
+    for (b, c) in boxes.iter().zip(colors) {
+        writeln!(ply, "{} {} {} {} {} {}", b.x, b.y, b.z, c.0, c.1, c.2).unwrap();
+    }
+    for &(bid1, bid2, _) in made {
+        writeln!(ply, "{} {}", bid1, bid2).unwrap();
+    }
+    ply
+}
 
-    println!("Product: {}", product);
+/// Writes the exports asked for on the command line.
+fn export(boxes: &[JBox], made: &[Connection], circuits: &UnionFind) {
+    let write = |path: &Path, contents: String| match fs::write(path, contents) {
+        Ok(()) => println!("Circuits written to {}", path.display()),
+        Err(e) => eprintln!("\x1b[31merror:\x1b[m {}: {}", path.display(), e),
+    };
+    if let Some(path) = options::get().dot.as_ref() {
+        write(path, circuits_dot(boxes, made, circuits));
+    }
+    if let Some(path) = options::get().ply.as_ref() {
+        write(path, circuits_ply(boxes, made, circuits));
+    }
 }
 
-fn solve_part_2(input: &str) {
-    let boxes = parse_input(input);
-    let mut pairs: Vec<(usize, usize, i64)> = Vec::new();
-    for b1 in 0..boxes.len() - 1 {
-        for b2 in b1 + 1..boxes.len() {
-            pairs.push((b1, b2, get_distance_squared(&boxes[b1], &boxes[b2])));
-        }
+fn solve_part_1(input: &str, connections: usize) -> Result<usize, ParseError> {
+    let boxes = parse_input(input)?;
+
+    let mut circuits = UnionFind::new(boxes.len());
+    let tree = box_tree(&boxes);
+    let made: Vec<Connection> = tree.nearest_pairs().take(connections).collect();
+    for &(bid1, bid2, _) in made.iter() {
+        circuits.union(bid1, bid2);
     }
 
-    pairs.sort_by(|p1, p2| p1.2.cmp(&p2.2));
-    // println!("{:?}", &pairs[..15]);
-
-    let mut circuits: Vec<Vec<usize>> = Vec::new();
-    circuits.push(vec![pairs[0].0, pairs[0].1]);
-    let mut pair_id = 0;
-
-    while !(circuits.len() == 1 && circuits.iter().map(|c| c.len()).sum::<usize>() == boxes.len()) {
-        pair_id += 1;
-        loop {
-            let (bid1, bid2, _) = pairs[pair_id];
-            let c1 = get_box_circuit(&circuits, bid1);
-            let c2 = get_box_circuit(&circuits, bid2);
-            if let Some(cid1) = c1 {
-                if let Some(cid2) = c2 {
-                    if cid1 == cid2 {
-                        break;
-                    }
-
-                    let mut new = Vec::new();
-                    new.append(&mut circuits[cid1]);
-                    new.append(&mut circuits[cid2]);
-                    circuits[cid1] = new;
-                    circuits.remove(cid2);
-                    break;
-                } else {
-                    circuits[cid1].push(bid2);
-                    break;
-                }
-            } else if let Some(cid2) = c2 {
-                circuits[cid2].push(bid1);
-                break;
-            } else {
-                circuits.push(vec![bid1, bid2]);
-                break;
-            }
-        }
+    if !options::get().spanning {
+        export(&boxes, &made, &circuits);
     }
 
-    let (bid1, bid2, _) = pairs[pair_id];
+    let mut sizes: Vec<usize> = circuits.components().iter().map(|c| c.len()).collect();
+    sizes.sort_by_key(|size| std::cmp::Reverse(*size));
 
-    let product = boxes[bid1].0 * boxes[bid2].0;
+    // fewer than three circuits have no product
+    let product = match sizes[..] {
+        [a, b, c, ..] => a * b * c,
+        _ => 0,
+    };
 
     println!("Product: {}", product);
+
+    Ok(product)
 }
 
-pub fn part_1() {
+fn solve_part_2(input: &str) -> Result<i64, ParseError> {
+    let boxes = parse_input(input)?;
+
+    let mut circuits = UnionFind::new(boxes.len());
+    let tree = box_tree(&boxes);
+    // only the connections joining two circuits, which span all the boxes
+    let mut made: Vec<Connection> = Vec::with_capacity(boxes.len().saturating_sub(1));
+    let last = tree.nearest_pairs().find(|&connection| {
+        if !circuits.union(connection.0, connection.1) {
+            return false;
+        }
+        made.push(connection);
+        circuits.component_count() == 1
+    });
+
+    // fewer than two boxes need no connection
+    let product = last.map_or(0, |(bid1, bid2, _)| boxes[bid1].x * boxes[bid2].x);
+
+    println!("Product: {}", product);
+
+    if options::get().spanning {
+        export(&boxes, &made, &circuits);
+    }
+
+    Ok(product)
+}
+
+pub fn part_1() -> Result<String, DayError> {
     let input = read_input(module_path!());
-    solve_part_1(input.as_str(), 1000);
+    solve_part_1(input.as_str(), 1000)
+        .map(|answer| answer.to_string())
+        .map_err(|e| e.locate(&input).into())
 }
 
-pub fn part_2() {
+pub fn part_2() -> Result<String, DayError> {
     let input = read_input(module_path!());
-    solve_part_2(input.as_str());
+    solve_part_2(input.as_str())
+        .map(|answer| answer.to_string())
+        .map_err(|e| e.locate(&input).into())
 }
 
 #[cfg(test)]
 mod test {
     use indoc::indoc;
 
+    use super::{Connection, JBox, UnionFind};
+
     const EXAMPLE_1: &str = indoc! {"
         162,817,812
         57,618,57
@@ -178,13 +207,68 @@ mod test {
 
     #[test]
     fn test_part_1() {
-        super::solve_part_1(EXAMPLE_1, 10);
+        super::solve_part_1(EXAMPLE_1, 10).unwrap();
     }
 
     const EXAMPLE_2: &str = EXAMPLE_1;
 
     #[test]
     fn test_part_2() {
-        super::solve_part_2(EXAMPLE_2);
+        super::solve_part_2(EXAMPLE_2).unwrap();
+    }
+
+    #[test]
+    fn test_few_boxes() {
+        assert_eq!(super::solve_part_1("", 10).unwrap(), 0);
+        assert_eq!(super::solve_part_1("1,2,3\n4,5,6\n", 10).unwrap(), 0);
+        assert_eq!(super::solve_part_2("").unwrap(), 0);
+        assert_eq!(super::solve_part_2("1,2,3\n").unwrap(), 0);
+        assert_eq!(super::solve_part_2("1,2,3\n4,5,6\n").unwrap(), 4);
+    }
+
+    /// The boxes, the first 10 connections and the circuits they make.
+    fn connect_10() -> (Vec<JBox>, Vec<Connection>, UnionFind) {
+        let boxes = super::parse_input(EXAMPLE_1).unwrap();
+        let made: Vec<_> = super::box_tree(&boxes).nearest_pairs().take(10).collect();
+        let mut circuits = UnionFind::new(boxes.len());
+        for &(bid1, bid2, _) in made.iter() {
+            circuits.union(bid1, bid2);
+        }
+        (boxes, made, circuits)
+    }
+
+    #[test]
+    fn test_circuits_dot() {
+        let (boxes, made, circuits) = connect_10();
+        let dot = super::circuits_dot(&boxes, &made, &circuits);
+
+        assert!(dot.starts_with("graph circuits {\n"));
+        assert_eq!(dot.matches(" -- ").count(), 10);
+        assert!(dot.contains("    0 -- 19 [label=\"316.9\"];\n"));
+        // box 0 is in the first circuit, box 1 is alone
+        assert!(dot.contains("    0 [label=\"0\\n162,817,812\" fillcolor=\"#e65c5c\"];\n"));
+        assert!(dot.contains("    1 [label=\"1\\n57,618,57\" fillcolor=\"#a0a0a0\"];\n"));
+    }
+
+    #[test]
+    fn test_circuits_ply() {
+        let (boxes, made, circuits) = connect_10();
+        let ply = super::circuits_ply(&boxes, &made, &circuits);
+
+        let (header, body) = ply.split_once("end_header\n").unwrap();
+        assert!(header.starts_with("ply\nformat ascii 1.0\nelement vertex 20\n"));
+        assert!(header.contains("element edge 10\n"));
+        let lines: Vec<&str> = body.lines().collect();
+        assert_eq!(lines.len(), 30);
+        assert_eq!(lines[0], "162 817 812 230 92 92");
+        assert_eq!(lines[1], "57 618 57 160 160 160");
+        assert_eq!(lines[20], "0 19");
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let input = "162,817,812\n57,618\n";
+        let err = super::parse_input(input).unwrap_err().locate(input);
+        assert_eq!((err.line, err.column, err.found.as_str()), (2, 1, "57,618"));
     }
 }
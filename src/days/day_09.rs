@@ -1,278 +1,179 @@
-use crate::utils::input::read_input;
-
-#[derive(Debug, Clone, Copy)]
-struct Point {
-    x: u64,
-    y: u64,
+use std::{fmt::Write, fs};
+
+use super::DayError;
+use crate::utils::{
+    compress::CompressedPolygon,
+    geom::{BoundingBox, Point2},
+    input::read_input,
+    options,
+    parse::ParseError,
+    polygon::RectilinearPolygon,
+    render::Rgb,
+};
+
+type Point = Point2<i64>;
+type Rect = BoundingBox<Point>;
+
+/// Number of tiles covered by the rectangle, borders included.
+fn area(r: &Rect) -> u64 {
+    let size = r.extent();
+    ((size.x + 1) * (size.y + 1)) as u64
 }
 
-#[derive(Debug, Clone, Copy)]
-struct Rect {
-    p1: Point,
-    p2: Point,
+fn parse_input(input: &str) -> Result<Vec<Point>, ParseError> {
+    input.lines().map(|line| line.parse()).collect()
 }
 
-impl Rect {
-    fn new(p1: &Point, p2: &Point) -> Self {
-        Rect {
-            p1: Point {
-                x: p1.x.min(p2.x),
-                y: p1.y.min(p2.y),
-            },
-            p2: Point {
-                x: p1.x.max(p2.x),
-                y: p1.y.max(p2.y),
-            },
+/// The largest rectangle with two red tiles as corners that `keep` accepts.
+fn largest_rect(points: &[Point], keep: impl Fn(&Rect) -> bool) -> Option<Rect> {
+    let mut best: Option<Rect> = None;
+    for p1 in 0..points.len() {
+        for p2 in p1..points.len() {
+            let r = Rect::from_corners(&points[p1], &points[p2]);
+            if best.as_ref().is_none_or(|b| area(&r) > area(b)) && keep(&r) {
+                best = Some(r);
+            }
         }
     }
-
-    fn area(&self) -> u64 {
-        (self.p1.x.abs_diff(self.p2.x) + 1) * (self.p1.y.abs_diff(self.p2.y) + 1)
-    }
+    best
 }
 
-#[derive(Debug, Clone, Copy)]
-struct Segment {
-    pos: u64,
-    start: u64,
-    end: u64,
-}
-
-impl Segment {
-    fn new(p1: &Point, p2: &Point) -> Segment {
-        if p1.x == p2.x {
-            Segment {
-                pos: p1.x,
-                start: p1.y.min(p2.y),
-                end: p1.y.max(p2.y),
-            }
-        } else {
-            Segment {
-                pos: p1.y,
-                start: p1.x.min(p2.x),
-                end: p1.x.max(p2.x),
-            }
+/// Size in pixels of the longest side of the SVG picture.
+const SVG_SIZE: f64 = 1000.0;
+
+/// An SVG picture of the loop of red tiles in input order, with the largest
+/// rectangle of each part drawn over it, colours being configurable as
+/// `loop`, `tile`, `part1` and `part2`. None if there are no tiles to draw.
+fn render_svg(
+    tiles: &RectilinearPolygon,
+    part_1: Option<&Rect>,
+    part_2: Option<&Rect>,
+) -> Option<String> {
+    let opts = options::get();
+    let points = tiles.vertices();
+    let bb = Rect::from_points(points)?;
+    let size = bb.extent() + Point2::new(1, 1);
+    let scale = SVG_SIZE / size.x.max(size.y) as f64;
+    // tiles are drawn as unit squares, so their centres are half a tile in
+    let x = |x: i64| (x - bb.min.x) as f64 * scale + scale / 2.0;
+    let y = |y: i64| (y - bb.min.y) as f64 * scale + scale / 2.0;
+
+    let mut svg = String::new();
+    writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{:.0}" height="{:.0}">"#,
+        size.x as f64 * scale,
+        size.y as f64 * scale
+    )
+    .unwrap();
+
+    let rects = [
+        (part_1, "part1", Rgb(0x40, 0x80, 0xe0)),
+        (part_2, "part2", Rgb(0x40, 0xc0, 0x60)),
+    ];
+    for (rect, name, default) in rects {
+        if let Some(r) = rect {
+            let crossing = if tiles.contains_rect(r) {
+                ""
+            } else {
+                " (crosses the loop)"
+            };
+            writeln!(
+                svg,
+                r#"  <rect x="{:.2}" y="{:.2}" width="{:.2}" height="{:.2}" fill="{}" fill-opacity="0.3"><title>{} {}{}</title></rect>"#,
+                x(r.min.x) - scale / 2.0,
+                y(r.min.y) - scale / 2.0,
+                (r.extent().x + 1) as f64 * scale,
+                (r.extent().y + 1) as f64 * scale,
+                opts.color(name, default),
+                name,
+                area(r),
+                crossing
+            )
+            .unwrap();
         }
     }
-}
 
-fn parse_input(input: &str) -> Vec<Point> {
-    input
-        .lines()
-        .map(|line| {
-            let mut parts = line.split(',');
-            Point {
-                x: parts.next().unwrap().parse().unwrap(),
-                y: parts.next().unwrap().parse().unwrap(),
-            }
-        })
-        .collect()
-}
-
-fn solve_part_1(input: &str) {
-    let points = parse_input(input);
-    let mut max_area = 0u64;
-    for p1 in 0..points.len() - 1 {
-        for p2 in p1..points.len() {
-            let r = Rect::new(&points[p1], &points[p2]);
-            let area = r.area();
-            if area > max_area {
-                max_area = area;
-            }
-        }
+    let corners: Vec<String> = points
+        .iter()
+        .map(|p| format!("{:.2},{:.2}", x(p.x), y(p.y)))
+        .collect();
+    writeln!(
+        svg,
+        r#"  <polygon points="{}" fill="none" stroke="{}"><title>loop {} tiles</title></polygon>"#,
+        corners.join(" "),
+        opts.color("loop", Rgb(0x40, 0x40, 0x40)),
+        tiles.lattice_points()
+    )
+    .unwrap();
+
+    let tile = opts.color("tile", Rgb(0xe0, 0x40, 0x40));
+    let radius = (scale / 2.0).max(2.0);
+    for p in points {
+        writeln!(
+            svg,
+            r#"  <circle cx="{:.2}" cy="{:.2}" r="{:.2}" fill="{}"><title>{}</title></circle>"#,
+            x(p.x),
+            y(p.y),
+            radius,
+            tile,
+            p
+        )
+        .unwrap();
     }
 
-    println!("Largest rectangle {}", max_area);
+    svg.push_str("</svg>\n");
+    Some(svg)
 }
 
-fn solve_part_2(input: &str) {
-    let mut points = parse_input(input);
-    points.push(points[0]); // close the loop
-    let mut vseg: Vec<_> = points
-        .iter()
-        .zip(points.iter().skip(1))
-        .filter_map(|(p1, p2)| {
-            if p1.x == p2.x {
-                Some(Segment::new(p1, p2))
-            } else {
-                None
-            }
-        })
-        .collect();
-    vseg.sort_by_key(|s| s.pos);
+fn solve_part_1(input: &str) -> Result<u64, ParseError> {
+    let points = parse_input(input)?;
+    let max_area = largest_rect(&points, |_| true).map_or(0, |r| area(&r));
 
-    let mut hseg: Vec<_> = points
-        .iter()
-        .zip(points.iter().skip(1))
-        .filter_map(|(p1, p2)| {
-            if p1.y == p2.y {
-                Some(Segment::new(p1, p2))
-            } else {
-                None
-            }
-        })
-        .collect();
-    hseg.sort_by_key(|s| s.pos);
-
-    // println!("{:?}", vseg);
-    // println!("{:?}", hseg);
-
-    let mut max_area = 0u64;
-    for p1 in 0..points.len() - 1 {
-        'next_rect: for p2 in p1 + 1..points.len() {
-            let r = Rect::new(&points[p1], &points[p2]);
-            // println!("");
-            // println!("{:?} {:?} ", &points[p1], &points[p2]);
-            // print!("{:?} ", r);
-
-            // top
-            let y = r.p1.y;
-            let mut inside = false;
-            let mut from = 0i8;
-            for v in vseg.iter() {
-                if v.pos >= r.p2.x {
-                    break;
-                }
-
-                if v.start > y || v.end < y {
-                    // not aligned
-                } else if v.start < y && v.end > y {
-                    inside = !inside; // unambiguously intersects
-                } else if from == 0 {
-                    // getting on the fence, remember where from
-                    if v.start == y {
-                        from = 1;
-                    } else {
-                        from = -1;
-                    }
-                } else {
-                    if v.start == y && from == -1 || v.end == y && from == 1 {
-                        inside = !inside; // crossing for good
-                    }
-                    from = 0;
-                }
-
-                if !inside && from == 0 && v.pos >= r.p1.x {
-                    continue 'next_rect;
-                }
-            }
+    println!("Largest rectangle {}", max_area);
 
-            // bottom
-            let y = r.p2.y;
-            let mut inside = false;
-            let mut from = 0i8;
-            for v in vseg.iter() {
-                if v.pos >= r.p2.x {
-                    break;
-                }
-
-                if v.start > y || v.end < y {
-                    // not aligned
-                } else if v.start < y && v.end > y {
-                    inside = !inside; // unambiguously intersects
-                } else if from == 0 {
-                    // getting on the fence, remember where from
-                    if v.start == y {
-                        from = 1;
-                    } else {
-                        from = -1;
-                    }
-                } else {
-                    if v.start == y && from == -1 || v.end == y && from == 1 {
-                        inside = !inside; // crossing for good
-                    }
-                    from = 0;
-                }
-
-                if !inside && from == 0 && v.pos >= r.p1.x {
-                    continue 'next_rect;
-                }
-            }
+    Ok(max_area)
+}
 
-            // left
-            let x = r.p1.x;
-            let mut inside = false;
-            let mut from = 0i8;
-            for h in hseg.iter() {
-                if h.pos >= r.p2.y {
-                    break;
-                }
-
-                if h.start > x || h.end < x {
-                    // not aligned
-                } else if h.start < x && h.end > x {
-                    inside = !inside; // unambiguously intersects
-                } else if from == 0 {
-                    // getting on the fence, remember where from
-                    if h.start == x {
-                        from = 1;
-                    } else {
-                        from = -1;
-                    }
-                } else {
-                    if h.start == x && from == -1 || h.end == x && from == 1 {
-                        inside = !inside; // crossing for good
-                    }
-                    from = 0;
-                }
-
-                if !inside && from == 0 && h.pos >= r.p1.y {
-                    continue 'next_rect;
-                }
-            }
+fn solve_part_2(input: &str) -> Result<u64, ParseError> {
+    let points = parse_input(input)?;
+    let tiles = RectilinearPolygon::new(points.clone()).map_err(|i| {
+        let line = input.lines().nth(i).unwrap();
+        ParseError::new(line, "a tile in line with the next one")
+    })?;
+    let compressed = CompressedPolygon::new(&tiles);
 
-            // right
-            let x = r.p2.x;
-            let mut inside = false;
-            let mut from = 0i8;
-            for h in hseg.iter() {
-                if h.pos >= r.p2.y {
-                    break;
-                }
-
-                if h.start > x || h.end < x {
-                    // not aligned
-                } else if h.start < x && h.end > x {
-                    inside = !inside; // unambiguously intersects
-                } else if from == 0 {
-                    // getting on the fence, remember where from
-                    if h.start == x {
-                        from = 1;
-                    } else {
-                        from = -1;
-                    }
-                } else {
-                    if h.start == x && from == -1 || h.end == x && from == 1 {
-                        inside = !inside; // crossing for good
-                    }
-                    from = 0;
-                }
-
-                if !inside && from == 0 && h.pos >= r.p1.y {
-                    continue 'next_rect;
-                }
-            }
+    let best = largest_rect(&points, |r| compressed.contains_rect(r));
+    let max_area = best.as_ref().map_or(0, area);
 
-            // print!(" is inside");
+    println!("Largest rectangle {}", max_area);
 
-            let area = r.area();
-            if area > max_area {
-                max_area = area;
-            }
+    if let Some(path) = options::get().svg.as_ref() {
+        let part_1 = largest_rect(&points, |_| true);
+        match render_svg(&tiles, part_1.as_ref(), best.as_ref()) {
+            None => println!("No tiles to draw"),
+            Some(svg) => match fs::write(path, svg) {
+                Ok(()) => println!("Tiles drawn to {}", path.display()),
+                Err(e) => eprintln!("\x1b[31merror:\x1b[m {}: {}", path.display(), e),
+            },
         }
     }
 
-    println!("Largest rectangle {}", max_area);
+    Ok(max_area)
 }
 
-pub fn part_1() {
+pub fn part_1() -> Result<String, DayError> {
     let input = read_input(module_path!());
-    solve_part_1(input.as_str());
+    solve_part_1(input.as_str())
+        .map(|answer| answer.to_string())
+        .map_err(|e| e.locate(&input).into())
 }
 
-pub fn part_2() {
+pub fn part_2() -> Result<String, DayError> {
     let input = read_input(module_path!());
-    solve_part_2(input.as_str());
+    solve_part_2(input.as_str())
+        .map(|answer| answer.to_string())
+        .map_err(|e| e.locate(&input).into())
 }
 
 #[cfg(test)]
@@ -292,13 +193,55 @@ mod test {
 
     #[test]
     fn test_part_1() {
-        super::solve_part_1(EXAMPLE_1);
+        super::solve_part_1(EXAMPLE_1).unwrap();
     }
 
     const EXAMPLE_2: &str = EXAMPLE_1;
 
     #[test]
     fn test_part_2() {
-        super::solve_part_2(EXAMPLE_2);
+        super::solve_part_2(EXAMPLE_2).unwrap();
+    }
+
+    #[test]
+    fn test_notch_one_tile_wide() {
+        // the notch at 13..=14 x 11..=12 holds no tile, so the whole loop
+        // but its spur is one rectangle
+        let input =
+            "13,10\n15,10\n15,11\n16,11\n16,12\n15,12\n15,13\n13,13\n13,12\n14,12\n14,11\n13,11\n";
+        assert_eq!(super::solve_part_2(input).unwrap(), 12);
+    }
+
+    #[test]
+    fn test_render_svg() {
+        let points = super::parse_input(EXAMPLE_1).unwrap();
+        let part_1 = super::largest_rect(&points, |_| true).unwrap();
+        let tiles = super::RectilinearPolygon::new(points.clone()).unwrap();
+        let svg = super::render_svg(&tiles, Some(&part_1), None).unwrap();
+
+        assert!(svg
+            .starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="1000" height="700">"#));
+        assert!(svg.ends_with("</svg>\n"));
+        assert_eq!(svg.matches("<circle").count(), points.len());
+        assert_eq!(svg.matches("<rect").count(), 1);
+        assert!(svg.contains("<title>part1 50 (crosses the loop)</title>"));
+        assert!(svg.contains("<title>loop 46 tiles</title>"));
+        // the first tile, 7,1, is 5 tiles right of the bounding box's top-left
+        // corner, 2,1, and on its top row
+        assert!(svg.contains(r#"<polygon points="550.00,50.00 "#));
+    }
+
+    #[test]
+    fn test_render_svg_empty() {
+        assert_eq!(super::solve_part_2("").unwrap(), 0);
+        let tiles = super::RectilinearPolygon::new(vec![]).unwrap();
+        assert_eq!(super::render_svg(&tiles, None, None), None);
+    }
+
+    #[test]
+    fn test_not_aligned() {
+        let input = "7,1\n11,1\n11,7\n9,6\n";
+        let err = super::solve_part_2(input).unwrap_err().locate(input);
+        assert_eq!((err.line, err.found.as_str()), (3, "11,7"));
     }
 }
@@ -1,212 +1,243 @@
-use std::collections::HashSet;
+use std::fmt::Write;
 
-use itertools::Itertools;
-use regex::bytes::Regex;
+use regex::Regex;
 
-use crate::utils::input::read_input;
+use super::DayError;
+use crate::utils::{
+    gf2::{self, BitVec},
+    ilp,
+    input::read_input,
+    options,
+    parse::{ints, list, ParseError},
+};
 
-fn parse_input(input: &str) -> Vec<(u16, Vec<u16>)> {
+type Machine = (Vec<Vec<u16>>, Vec<u16>);
+
+const MACHINE: &str = "[lights] (buttons) {joltages}";
+
+/// Parses the indices of each button, checking that they match one of the `n` lights.
+fn parse_buttons(buttons: &str, n: usize) -> Result<Vec<Vec<u16>>, ParseError> {
+    buttons
+        .split(' ')
+        .map(|b| {
+            let ids: Vec<u16> = ints(b)?;
+            if ids.iter().any(|id| *id as usize >= n) {
+                return Err(ParseError::new(b, format!("light indices below {}", n)));
+            }
+            Ok(ids)
+        })
+        .collect()
+}
+
+fn parse_input(input: &str) -> Result<Vec<(u16, Vec<u16>)>, ParseError> {
     input
         .lines()
         .map(|line| {
-            let re = Regex::new(r"\[(.*)\] (.*) \{").unwrap();
-            let cap = re.captures(line.as_bytes()).unwrap();
-            let lights = cap.get(1).unwrap();
-            let buttons = cap.get(2).unwrap();
-            let lights: u16 = lights
-                .as_bytes()
-                .into_iter()
+            let re = Regex::new(r"^\[([.#]{0,16})\] (.*) \{").unwrap();
+            let cap = re
+                .captures(line)
+                .ok_or_else(|| ParseError::new(line, MACHINE))?;
+            let lights: u16 = cap[1]
+                .bytes()
                 .rev()
-                .fold(0, |acc, l| acc * 2 + (*l == b'#') as u16);
-            let buttons = buttons.as_bytes().split(|b| *b == b' ');
-            let buttons: Vec<u16> = buttons
-                .map(|b| {
-                    let bits = b[1..b.len() - 1].split(|c| *c == b',');
-                    bits.fold(0, |acc, b| acc + (1 << (b[0] - b'0')))
-                })
+                .fold(0, |acc, l| acc * 2 + (l == b'#') as u16);
+            let buttons: Vec<u16> = parse_buttons(cap.get(2).unwrap().as_str(), cap[1].len())?
+                .into_iter()
+                .map(|ids| ids.into_iter().fold(0, |acc, l| acc + (1 << l)))
                 .collect();
 
-            (lights, buttons)
+            Ok((lights, buttons))
         })
         .collect()
 }
 
-fn get_min_presses(lights: u16, buttons: &Vec<u16>) -> usize {
-    for n in 1..buttons.len() {
-        if buttons
-            .iter()
-            .combinations(n)
-            .any(|buttons| buttons.iter().fold(0, |acc, b| acc ^ *b) == lights)
-        {
-            return n;
-        }
-    }
+/// The buttons to press to turn on exactly `lights` with the fewest presses,
+/// or `None` if no combination of buttons does.
+fn min_presses(lights: u16, buttons: &[u16]) -> Option<Vec<usize>> {
+    // one equation per light: the buttons toggling it must add up to its state
+    let rows: Vec<BitVec> = (0..16)
+        .map(|l| buttons.iter().map(|b| b >> l & 1 == 1).collect())
+        .collect();
+    let target = BitVec::from_mask(lights as u64, 16);
+    gf2::solve(&rows, &target).map(|solutions| solutions.min_weight().ones().collect())
+}
 
-    return 0;
+/// The fewest button presses that turn on exactly `lights`, or `None` if no
+/// combination of buttons does.
+fn get_min_presses(lights: u16, buttons: &[u16]) -> Option<usize> {
+    min_presses(lights, buttons).map(|pressed| pressed.len())
 }
 
-fn solve_part_1(input: &str) {
-    let configs = parse_input(input);
-    let min_presses: usize = configs
-        .iter()
-        .map(|(lights, buttons)| get_min_presses(*lights, buttons))
-        .sum();
+fn solve_part_1(input: &str) -> Result<usize, DayError> {
+    if let Some(machine) = options::get().inspect.as_deref() {
+        match inspect(machine) {
+            Ok(view) => print!("{}", view),
+            Err(e) => {
+                let e = e.locate(machine);
+                eprintln!("\x1b[31merror:\x1b[m {}", e);
+                eprint!("{}", e.snippet());
+            }
+        }
+    }
+
+    let configs = parse_input(input)?;
+    let mut min_presses = 0;
+    for (i, (line, (lights, buttons))) in input.lines().zip(configs.iter()).enumerate() {
+        min_presses += get_min_presses(*lights, buttons).ok_or_else(|| {
+            DayError::no_solution(i + 1, line, "no buttons turn on exactly its lights")
+        })?;
+    }
 
     println!("Fewest presses: {}", min_presses);
+
+    Ok(min_presses)
 }
 
-fn parse_input_v2(input: &str) -> Vec<(Vec<Vec<u16>>, Vec<u16>)> {
+fn parse_input_v2(input: &str) -> Result<Vec<Machine>, ParseError> {
     input
         .lines()
         .map(|line| {
-            let re = Regex::new(r"\[(.*)\] (.*) \{(.*)\}").unwrap();
-            let cap = re.captures(line.as_bytes()).unwrap();
-            let buttons = cap.get(2).unwrap();
-            let jolts = cap.get(3).unwrap();
-            let buttons = buttons.as_bytes().split(|b| *b == b' ');
-            let buttons: Vec<Vec<u16>> = buttons
-                .map(|b| {
-                    let bids = b[1..b.len() - 1].split(|c| *c == b',');
-                    bids.map(|bid| (bid[0] - b'0') as u16).collect()
-                })
-                .collect();
-            let jolts = jolts.as_bytes().split(|b| *b == b',');
-            let jolts = jolts
-                .map(|j| str::from_utf8(j).unwrap().parse::<u16>().unwrap())
-                .collect();
+            let re = Regex::new(r"^\[(.*)\] (.*) \{(.*)\}$").unwrap();
+            let cap = re
+                .captures(line)
+                .ok_or_else(|| ParseError::new(line, MACHINE))?;
+            let jolts: Vec<u16> = list(&cap[3], ',')?;
+            let buttons = parse_buttons(&cap[2], jolts.len())?;
 
-            (buttons, jolts)
+            Ok((buttons, jolts))
         })
         .collect()
 }
 
-fn fits_jolts(buttons: &Vec<Vec<u16>>, jolts: &Vec<u16>, branch: &Vec<u16>) -> bool {
-    let mut calculated_jolts: Vec<u16> = vec![0; jolts.len()];
-    for (bid, presses) in branch.iter().enumerate() {
-        for jid in buttons[bid].iter() {
-            calculated_jolts[*jid as usize] += presses;
-        }
-    }
-    for i in 0..jolts.len() {
-        if jolts[i] != calculated_jolts[i] {
-            return false;
-        }
-    }
-    true
+/// How many times to press each button to bring every counter to its
+/// joltage with the fewest presses, or `None` if no combination does.
+fn min_jolt_presses(buttons: &[Vec<u16>], jolts: &[u16]) -> Option<Vec<i64>> {
+    // one equation per counter: the presses of the buttons incrementing it
+    // must add up to its joltage
+    let rows: Vec<Vec<i64>> = (0..jolts.len() as u16)
+        .map(|j| buttons.iter().map(|b| b.contains(&j) as i64).collect())
+        .collect();
+    let jolts: Vec<i64> = jolts.iter().map(|&j| j as i64).collect();
+    ilp::min_sum(&rows, &jolts)
 }
 
-fn exceeds_jolts(buttons: &Vec<Vec<u16>>, jolts: &Vec<u16>, branch: &Vec<u16>) -> bool {
-    let mut calculated_jolts: Vec<u16> = vec![0; jolts.len()];
-    for (bid, presses) in branch.iter().enumerate() {
-        for jid in buttons[bid].iter() {
-            let jid = *jid as usize;
-            calculated_jolts[jid] += presses;
-            if calculated_jolts[jid] > jolts[jid] {
-                return true;
-            }
-        }
-    }
-    false
+/// The fewest button presses that bring every counter to its joltage, or
+/// `None` if no combination of presses does.
+fn get_min_jolt_presses(buttons: &[Vec<u16>], jolts: &[u16]) -> Option<usize> {
+    min_jolt_presses(buttons, jolts).map(|presses| presses.iter().sum::<i64>() as usize)
 }
 
-fn get_min_presses_v2(buttons: &Vec<Vec<u16>>, jolts: &Vec<u16>) -> usize {
-    let mut branches: HashSet<Vec<u16>> = HashSet::new();
-    branches.insert(vec![0; buttons.len()]);
-    let mut presses = 0;
-    loop {
-        presses += 1;
-        println!("Turn: {}, branches: {}", presses, branches.len());
-        let mut new_branches: HashSet<Vec<u16>> = HashSet::new();
-        for b in branches.iter() {
-            for n in 0..buttons.len() {
-                let mut new = b.clone();
-                new[n] += 1;
-                if fits_jolts(buttons, jolts, &new) {
-                    return presses;
-                }
-                if !exceeds_jolts(buttons, jolts, &new) {
-                    new_branches.insert(new);
-                }
-            }
-        }
-        branches = new_branches;
-    }
+/// Draws `n` lights as `#` when on and `.` when off.
+fn pattern(n: usize, on: impl Fn(usize) -> bool) -> String {
+    (0..n).map(|l| if on(l) { '#' } else { '.' }).collect()
 }
 
-fn get_highest_score_button_id(buttons: &Vec<Vec<u16>>, jolts: &Vec<u16>) -> usize {
-    buttons
+/// Everything about the machine `line`: its target, the lights each button
+/// toggles and its joltages, then the presses each part chooses with the
+/// state after every single press. Fails unless `line` is one machine.
+fn inspect(line: &str) -> Result<String, ParseError> {
+    let mut lines = line.lines();
+    let (Some(line), None) = (lines.next(), lines.next()) else {
+        let extra = line.lines().nth(1).unwrap_or(line);
+        return Err(ParseError::new(extra, "a single machine"));
+    };
+    let (lights, masks) = parse_input(line)?.remove(0);
+    let (buttons, jolts) = parse_input_v2(line)?.remove(0);
+    // the lights pattern, which parse_input checked, comes first in brackets
+    let n = line.find(']').unwrap() - 1;
+    let labels: Vec<String> = buttons
         .iter()
         .enumerate()
-        .filter_map(|(bid, butt)| {
-            if butt.iter().any(|bid| jolts[*bid as usize] == 0) {
-                None
-            } else {
-                Some((
-                    bid,
-                    butt.iter()
-                        .map(|jid| {
-                            let j = jolts[*jid as usize] as u64;
-                            j * j
-                        })
-                        .sum::<u64>(),
-                ))
-            }
+        .map(|(i, b)| {
+            let ids: Vec<String> = b.iter().map(|j| j.to_string()).collect();
+            format!("{:>2} ({})", i, ids.join(","))
         })
-        .inspect(|(bid, score)| println!("Score {} for {:?}", *score, buttons[*bid]))
-        .max_by_key(|(_, score)| *score)
-        .unwrap()
-        .0
-}
+        .collect();
+    let width = labels.iter().map(|l| l.len()).max().unwrap_or(0);
 
-fn push_button(jolts: &mut Vec<u16>, button: &Vec<u16>) {
-    for b in button.iter() {
-        jolts[*b as usize] -= 1;
+    let mut view = String::new();
+    writeln!(view, "target    {}", pattern(n, |l| lights >> l & 1 == 1)).unwrap();
+    for (i, mask) in masks.iter().enumerate() {
+        writeln!(
+            view,
+            "button {:<2} {}",
+            i,
+            pattern(n, |l| mask >> l & 1 == 1)
+        )
+        .unwrap();
+    }
+    writeln!(view, "joltages  {:?}", jolts).unwrap();
+
+    match min_presses(lights, &masks) {
+        Some(pressed) => {
+            writeln!(view, "part 1: {} presses", pressed.len()).unwrap();
+            let mut state = 0u16;
+            for i in pressed {
+                state ^= masks[i];
+                writeln!(
+                    view,
+                    "  {:<width$} -> {}",
+                    labels[i],
+                    pattern(n, |l| state >> l & 1 == 1),
+                )
+                .unwrap();
+            }
+        }
+        None => writeln!(view, "part 1: no solution").unwrap(),
     }
-}
 
-fn get_min_presses_v3(buttons: &Vec<Vec<u16>>, jolts: &Vec<u16>) -> usize {
-    let mut jolts = jolts.clone();
-    let mut turns = 0;
-    while jolts.iter().any(|&j| j != 0) {
-        turns += 1;
-        println!("Turn {}: {:?}", turns, jolts);
-        let bid = get_highest_score_button_id(buttons, &jolts);
-        push_button(&mut jolts, &buttons[bid]);
+    match min_jolt_presses(&buttons, &jolts) {
+        Some(presses) => {
+            writeln!(view, "part 2: {} presses", presses.iter().sum::<i64>()).unwrap();
+            let mut counters = vec![0; jolts.len()];
+            for (i, &times) in presses.iter().enumerate() {
+                for _ in 0..times {
+                    buttons[i].iter().for_each(|&j| counters[j as usize] += 1);
+                    writeln!(view, "  {:<width$} -> {:?}", labels[i], counters).unwrap();
+                }
+            }
+        }
+        None => writeln!(view, "part 2: no solution").unwrap(),
     }
 
-    println!("{turns} turns");
-    turns
+    Ok(view)
 }
 
-fn solve_part_2(input: &str) {
-    let configs = parse_input_v2(input);
-    let min_presses: usize = configs
-        .iter()
-        .enumerate()
-        .map(|(n, bj)| {
-            print!("Line {}: ", n);
-            bj
-        })
-        .map(|(buttons, jolts)| get_min_presses_v3(buttons, jolts))
-        .sum();
+fn solve_part_2(input: &str) -> Result<usize, DayError> {
+    let configs = parse_input_v2(input)?;
+    let mut min_presses = 0;
+    for (i, (line, (buttons, jolts))) in input.lines().zip(configs.iter()).enumerate() {
+        min_presses += get_min_jolt_presses(buttons, jolts).ok_or_else(|| {
+            DayError::no_solution(i + 1, line, "no presses bring its counters to its joltages")
+        })?;
+    }
 
     println!("Fewest presses: {}", min_presses);
+
+    Ok(min_presses)
 }
 
-pub fn part_1() {
+pub fn part_1() -> Result<String, DayError> {
     let input = read_input(module_path!());
-    solve_part_1(input.as_str());
+    solve_part_1(input.as_str())
+        .map(|answer| answer.to_string())
+        .map_err(|e| e.locate(&input))
 }
 
-pub fn part_2() {
+pub fn part_2() -> Result<String, DayError> {
     let input = read_input(module_path!());
-    solve_part_2(input.as_str());
+    solve_part_2(input.as_str())
+        .map(|answer| answer.to_string())
+        .map_err(|e| e.locate(&input))
 }
 
 #[cfg(test)]
 mod test {
     use indoc::indoc;
 
+    use super::DayError;
+    use crate::utils::search;
+
     const EXAMPLE_1: &str = indoc! {"
         [.##.] (3) (1,3) (2) (2,3) (0,2) (0,1) {3,5,4,7}
         [...#.] (0,2,3,4) (2,3) (0,4) (0,1,2) (1,2,3,4) {7,5,12,7,2}
@@ -215,13 +246,117 @@ mod test {
 
     #[test]
     fn test_part_1() {
-        super::solve_part_1(EXAMPLE_1);
+        super::solve_part_1(EXAMPLE_1).unwrap();
+    }
+
+    #[test]
+    fn test_unsolvable() {
+        let input = "[.##.] (3) (1,3) (2) (2,3) (0,2) (0,1) {3,5,4,7}\n[#.] (1) {1,2}\n";
+        let err = super::solve_part_1(input).unwrap_err();
+        assert!(matches!(err, DayError::NoSolution { line: 2, .. }));
+        assert_eq!(err.snippet(), "  |\n2 | [#.] (1) {1,2}\n");
+
+        let input = "[#.] (0,1) {1,2}\n";
+        let err = super::solve_part_2(input).unwrap_err();
+        assert!(matches!(err, DayError::NoSolution { line: 1, .. }));
     }
 
     const EXAMPLE_2: &str = EXAMPLE_1;
 
     #[test]
     fn test_part_2() {
-        super::solve_part_2(EXAMPLE_2);
+        super::solve_part_2(EXAMPLE_2).unwrap();
+    }
+
+    #[test]
+    fn test_inspect() {
+        let line = EXAMPLE_1.lines().next().unwrap();
+        let expected = indoc! {"
+            target    .##.
+            button 0  ...#
+            button 1  .#.#
+            button 2  ..#.
+            button 3  ..##
+            button 4  #.#.
+            button 5  ##..
+            joltages  [3, 5, 4, 7]
+            part 1: 2 presses
+               1 (1,3) -> .#.#
+               3 (2,3) -> .##.
+            part 2: 10 presses
+               0 (3)   -> [0, 0, 0, 1]
+               1 (1,3) -> [0, 1, 0, 2]
+               1 (1,3) -> [0, 2, 0, 3]
+               3 (2,3) -> [0, 2, 1, 4]
+               3 (2,3) -> [0, 2, 2, 5]
+               3 (2,3) -> [0, 2, 3, 6]
+               3 (2,3) -> [0, 2, 4, 7]
+               5 (0,1) -> [1, 3, 4, 7]
+               5 (0,1) -> [2, 4, 4, 7]
+               5 (0,1) -> [3, 5, 4, 7]
+        "};
+        assert_eq!(super::inspect(line).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_inspect_not_one_machine() {
+        let err = super::inspect("").unwrap_err();
+        assert_eq!(err.expected, "a single machine");
+
+        let err = super::inspect(EXAMPLE_1).unwrap_err().locate(EXAMPLE_1);
+        assert_eq!(err.line, 2);
+    }
+
+    #[test]
+    fn test_inspect_more_lights_than_counters() {
+        let view = super::inspect("[#..] (0) (1) {1,2}").unwrap();
+        let expected = indoc! {"
+            target    #..
+            button 0  #..
+            button 1  .#.
+            joltages  [1, 2]
+            part 1: 1 presses
+               0 (0) -> #..
+            part 2: 3 presses
+               0 (0) -> [1, 0]
+               1 (1) -> [1, 1]
+               1 (1) -> [1, 2]
+        "};
+        assert_eq!(view, expected);
+    }
+
+    /// The fewest button presses that bring every counter to its joltage, by
+    /// searching through the counter states. Far slower than
+    /// `get_min_jolt_presses`, but easy to trust.
+    fn get_min_presses_v2(buttons: &[Vec<u16>], jolts: &[u16]) -> Option<usize> {
+        let press = |counters: &Vec<u16>| {
+            buttons
+                .iter()
+                .filter(|b| b.iter().all(|&j| counters[j as usize] < jolts[j as usize]))
+                .map(|b| {
+                    let mut next = counters.clone();
+                    b.iter().for_each(|&j| next[j as usize] += 1);
+                    (next, 1)
+                })
+                .collect::<Vec<_>>()
+        };
+        // a press adds at most 1 to each counter
+        let left = |counters: &Vec<u16>| {
+            let gaps = counters.iter().zip(jolts).map(|(c, j)| (j - c) as usize);
+            gaps.max().unwrap_or(0)
+        };
+
+        let outcome = search::astar(vec![0; jolts.len()], press, left, |c| c == jolts);
+        outcome.goal.map(|(_, presses)| presses)
+    }
+
+    #[test]
+    fn test_search_agrees() {
+        for (buttons, jolts) in super::parse_input_v2(EXAMPLE_2).unwrap() {
+            assert_eq!(
+                get_min_presses_v2(&buttons, &jolts),
+                super::get_min_jolt_presses(&buttons, &jolts)
+            );
+        }
     }
 }
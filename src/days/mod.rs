@@ -11,7 +11,80 @@ mod day_10;
 // mod day_11;
 // mod day_12;
 
-pub const ALL_DAYS: &[(fn(), fn())] = &[
+use std::fmt;
+
+use crate::utils::parse::ParseError;
+
+/// Why a day gave no answer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DayError {
+    /// The input is malformed.
+    Parse(ParseError),
+    /// The input is well formed, but what line `line` describes has no answer.
+    NoSolution {
+        line: usize,
+        source_line: String,
+        reason: String,
+    },
+}
+
+impl DayError {
+    /// An error for line `line` of the input, counting from 1, whose text is
+    /// `source_line`.
+    pub fn no_solution(line: usize, source_line: &str, reason: impl Into<String>) -> Self {
+        DayError::NoSolution {
+            line,
+            source_line: source_line.to_owned(),
+            reason: reason.into(),
+        }
+    }
+
+    /// Same as [`ParseError::locate`], errors without a solution being
+    /// located already.
+    pub fn locate(self, input: &str) -> Self {
+        match self {
+            DayError::Parse(e) => DayError::Parse(e.locate(input)),
+            e => e,
+        }
+    }
+
+    /// The offending line, underlined for parse errors.
+    pub fn snippet(&self) -> String {
+        match self {
+            DayError::Parse(e) => e.snippet(),
+            DayError::NoSolution {
+                line, source_line, ..
+            } => {
+                let number = line.to_string();
+                let gutter = " ".repeat(number.len());
+                format!("{} |\n{} | {}\n", gutter, number, source_line)
+            }
+        }
+    }
+}
+
+impl From<ParseError> for DayError {
+    fn from(e: ParseError) -> Self {
+        DayError::Parse(e)
+    }
+}
+
+impl fmt::Display for DayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DayError::Parse(e) => write!(f, "{}", e),
+            DayError::NoSolution { line, reason, .. } => {
+                write!(f, "no solution at line {}: {}", line, reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DayError {}
+
+pub type Solver = fn() -> Result<String, DayError>;
+
+pub const ALL_DAYS: &[(Solver, Solver)] = &[
     (day_01::part_1, day_01::part_2),
     (day_02::part_1, day_02::part_2),
     (day_03::part_1, day_03::part_2),
@@ -25,3 +98,20 @@ pub const ALL_DAYS: &[(fn(), fn())] = &[
     // (day_11::part_1, day_11::part_2),
     // (day_12::part_1, day_12::part_2),
 ];
+
+/// Runs both parts of day `day`, counting from 1, printing their errors
+/// with the offending line.
+pub fn run(day: usize) {
+    let (pt1, pt2) = ALL_DAYS[day - 1];
+    println!("\x1b[30m========\x1b[m");
+    println!("\x1b[32m# DAY {:02}\x1b[m", day);
+    for (i, part) in [pt1, pt2].into_iter().enumerate() {
+        println!("\x1b[30m--------\x1b[m");
+        println!("\x1b[34m## Part {}\x1b[m", i + 1);
+        if let Err(e) = part() {
+            eprintln!("\x1b[31merror:\x1b[m {}", e);
+            eprint!("{}", e.snippet());
+        }
+    }
+    println!();
+}
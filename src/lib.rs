@@ -1,2 +1,5 @@
+pub mod client;
 pub mod days;
 mod utils;
+
+pub use utils::options;
@@ -1,15 +1,75 @@
-use aoc_2025::days::ALL_DAYS;
+use std::{env, process};
+
+use aoc_2025::{
+    client::Client,
+    days::{self, ALL_DAYS},
+    options,
+};
+
+fn run_all() {
+    for day in 1..=ALL_DAYS.len() {
+        days::run(day);
+    }
+}
+
+fn fetch(day: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let day: u8 = day.parse()?;
+    let path = Client::from_env()?.fetch_input(day)?;
+    println!("Input for day {} is in {}", day, path.display());
+    Ok(())
+}
+
+fn submit(day: &str, part: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let day: u8 = day.parse()?;
+    let part: u8 = part.parse()?;
+    let client = Client::from_env()?;
+    let (pt1, pt2) = ALL_DAYS
+        .get((day as usize).wrapping_sub(1))
+        .ok_or(format!("day {} is not solved yet", day))?;
+
+    let answer = match part {
+        1 => pt1(),
+        2 => pt2(),
+        _ => return Err(format!("there is no part {}", part).into()),
+    }
+    .map_err(|e| format!("{}\n{}", e, e.snippet().trim_end()))?;
+    println!("Submitting {} for day {} part {}", answer, day, part);
+    let outcome = client.submit(day, part, &answer)?;
+    println!("{}", outcome);
+    Ok(())
+}
+
+fn usage() -> ! {
+    eprintln!(
+        "usage: aoc-2025 [fetch <day> | submit <day> <part>] {}",
+        options::USAGE
+    );
+    process::exit(2);
+}
 
 fn main() {
-    for (i, (pt1, pt2)) in ALL_DAYS.iter().enumerate() {
-        println!("\x1b[30m========\x1b[m");
-        println!("\x1b[32m# DAY {:02}\x1b[m", i + 1);
-        println!("\x1b[30m--------\x1b[m");
-        println!("\x1b[34m## Part 1\x1b[m");
-        pt1();
-        println!("\x1b[30m--------\x1b[m");
-        println!("\x1b[34m## Part 2\x1b[m");
-        pt2();
-        println!();
+    let args: Vec<String> = env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(|a| a.as_str()).collect();
+    let args = match options::init(&args) {
+        Ok(rest) => rest,
+        Err(e) => {
+            eprintln!("\x1b[31merror:\x1b[m {}", e);
+            usage();
+        }
+    };
+
+    let result = match args.as_slice() {
+        [] => {
+            run_all();
+            Ok(())
+        }
+        ["fetch", day] => fetch(day),
+        ["submit", day, part] => submit(day, part),
+        _ => usage(),
+    };
+
+    if let Err(e) = result {
+        eprintln!("\x1b[31merror:\x1b[m {}", e);
+        process::exit(1);
     }
 }
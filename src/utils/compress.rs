@@ -0,0 +1,409 @@
+//! Coordinate compression, for shapes whose coordinates are huge but few.
+
+use std::collections::VecDeque;
+
+use super::{
+    geom::{BoundingBox, Point2},
+    grid::{Grid, Pos},
+    polygon::RectilinearPolygon,
+};
+
+type Point = Point2<i64>;
+
+/// The distinct values of one coordinate, and the cells they split the axis
+/// into: one cell per value, and one per gap around them.
+///
+/// With values `3, 8`, cell 0 is everything below 3, cell 1 is 3, cell 2 is
+/// `4..=7`, cell 3 is 8 and cell 4 everything above. The gap between two
+/// consecutive integers still has its cell, which holds no integer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Axis {
+    values: Vec<i64>,
+}
+
+impl Axis {
+    pub fn new(values: impl IntoIterator<Item = i64>) -> Self {
+        let mut values: Vec<i64> = values.into_iter().collect();
+        values.sort_unstable();
+        values.dedup();
+        Axis { values }
+    }
+
+    #[allow(dead_code)]
+    pub fn values(&self) -> &[i64] {
+        &self.values
+    }
+
+    /// The number of cells.
+    pub fn cells(&self) -> usize {
+        2 * self.values.len() + 1
+    }
+
+    /// Whether cell `i` holds an integer, which only the gap between two
+    /// consecutive integers doesn't.
+    pub fn has_integers(&self, i: usize) -> bool {
+        i % 2 == 1
+            || i == 0
+            || i == 2 * self.values.len()
+            || self.values[i / 2] - self.values[i / 2 - 1] > 1
+    }
+
+    /// The cell holding `v`, whether it is one of the values or not.
+    pub fn cell(&self, v: i64) -> usize {
+        let below = self.values.partition_point(|&u| u < v);
+        if self.values.get(below) == Some(&v) {
+            2 * below + 1
+        } else {
+            2 * below
+        }
+    }
+}
+
+/// Sums over rectangles of a grid, each in constant time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrefixSums {
+    width: usize,
+    /// At `(x, y)`, the sum of the cells above and left of it, with an extra
+    /// row and column of zeros first.
+    sums: Vec<i64>,
+}
+
+impl PrefixSums {
+    pub fn new<T>(grid: &Grid<T>, weight: impl Fn(&T) -> i64) -> Self {
+        let width = grid.width() + 1;
+        let mut sums = vec![0; width * (grid.height() + 1)];
+        for ((x, y), cell) in grid.iter() {
+            sums[(y + 1) * width + x + 1] =
+                weight(cell) + sums[y * width + x + 1] + sums[(y + 1) * width + x]
+                    - sums[y * width + x];
+        }
+        PrefixSums { width, sums }
+    }
+
+    /// The sum of the cells from `min` to `max`, both included.
+    pub fn sum(&self, min: Pos, max: Pos) -> i64 {
+        let at = |x: usize, y: usize| self.sums[y * self.width + x];
+        at(max.0 + 1, max.1 + 1) - at(min.0, max.1 + 1) - at(max.0 + 1, min.1) + at(min.0, min.1)
+    }
+}
+
+/// A [`RectilinearPolygon`] rasterized onto the cells of its compressed
+/// coordinates, where a cell is inside if all its points are.
+///
+/// Rectangles are made of tiles, the points with integer coordinates, so a
+/// rectangle is inside when all its cells holding a tile are.
+#[derive(Debug, Clone)]
+pub struct CompressedPolygon {
+    xs: Axis,
+    ys: Axis,
+    #[allow(dead_code)]
+    inside: Grid<bool>,
+    counts: PrefixSums,
+}
+
+impl CompressedPolygon {
+    pub fn new(poly: &RectilinearPolygon) -> Self {
+        let xs = Axis::new(poly.vertices().iter().map(|v| v.x));
+        let ys = Axis::new(poly.vertices().iter().map(|v| v.y));
+        let cell = |p: &Point| (xs.cell(p.x), ys.cell(p.y));
+
+        // draw the boundary, then flood the outside from a corner, which is
+        // always outside thanks to the cells around the values
+        let mut boundary = Grid::new(xs.cells(), ys.cells(), false);
+        let vertices = poly.vertices();
+        for (i, v) in vertices.iter().enumerate() {
+            let (a, b) = (cell(v), cell(&vertices[(i + 1) % vertices.len()]));
+            for x in a.0.min(b.0)..=a.0.max(b.0) {
+                for y in a.1.min(b.1)..=a.1.max(b.1) {
+                    boundary.set((x, y), true);
+                }
+            }
+        }
+
+        let mut outside = Grid::new(xs.cells(), ys.cells(), false);
+        let mut todo = VecDeque::from([(0, 0)]);
+        outside.set((0, 0), true);
+        while let Some(pos) = todo.pop_front() {
+            for next in boundary.neighbours_4(pos) {
+                if !boundary.get(next).unwrap() && outside.set(next, true) == Some(false) {
+                    todo.push_back(next);
+                }
+            }
+        }
+
+        let inside = outside.map(|out| !out);
+        // the cells between two consecutive integers, even outside, leave
+        // no tile out of a rectangle spanning them
+        let mut covered = inside.clone();
+        for ((x, y), _) in inside.iter() {
+            if !xs.has_integers(x) || !ys.has_integers(y) {
+                covered.set((x, y), true);
+            }
+        }
+        let counts = PrefixSums::new(&covered, |&is_in| is_in as i64);
+        CompressedPolygon {
+            xs,
+            ys,
+            inside,
+            counts,
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn xs(&self) -> &Axis {
+        &self.xs
+    }
+
+    #[allow(dead_code)]
+    pub fn ys(&self) -> &Axis {
+        &self.ys
+    }
+
+    /// The rasterized polygon, one cell per [`Axis`] cell.
+    #[allow(dead_code)]
+    pub fn inside(&self) -> &Grid<bool> {
+        &self.inside
+    }
+
+    #[allow(dead_code)]
+    pub fn contains_point(&self, p: &Point) -> bool {
+        *self
+            .inside
+            .get((self.xs.cell(p.x), self.ys.cell(p.y)))
+            .unwrap()
+    }
+
+    /// Whether every tile of `r`, borders included, is inside.
+    pub fn contains_rect(&self, r: &BoundingBox<Point>) -> bool {
+        let min = (self.xs.cell(r.min.x), self.ys.cell(r.min.y));
+        let max = (self.xs.cell(r.max.x), self.ys.cell(r.max.y));
+        let cells = (max.0 - min.0 + 1) * (max.1 - min.1 + 1);
+        self.counts.sum(min, max) == cells as i64
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::{HashMap, HashSet};
+
+    use super::{Axis, CompressedPolygon, PrefixSums};
+    use crate::utils::{
+        geom::{BoundingBox, Point2},
+        grid::Grid,
+        polygon::RectilinearPolygon,
+        rng::XorShift,
+    };
+
+    type Point = Point2<i64>;
+
+    /// Whether every tile of `r` is inside `poly`, one tile at a time.
+    fn tiles_inside(poly: &RectilinearPolygon, r: &BoundingBox<Point>) -> bool {
+        (r.min.x..=r.max.x)
+            .all(|x| (r.min.y..=r.max.y).all(|y| poly.contains_point(&Point2::new(x, y))))
+    }
+
+    /// The outline of a random blob of unit squares on a `size` by `size`
+    /// board, or `None` if the blob has a hole or two squares touching only
+    /// by a corner. The lines between squares are 1 or 2 apart, so that some
+    /// edges of the outline are next to each other.
+    fn random_loop(rng: &mut XorShift, size: i64) -> Option<Vec<Point>> {
+        let mut blob = HashSet::from([(size / 2, size / 2)]);
+        for _ in 0..rng.below(3 * size as u64) {
+            let (x, y) = (rng.below(size as u64) as i64, rng.below(size as u64) as i64);
+            let near = [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)];
+            if near.iter().any(|n| blob.contains(n)) {
+                blob.insert((x, y));
+            }
+        }
+
+        let filled = |x: i64, y: i64| blob.contains(&(x, y));
+        for x in -1..size {
+            for y in -1..size {
+                let (a, b) = (filled(x, y), filled(x + 1, y + 1));
+                if a == b && filled(x + 1, y) == filled(x, y + 1) && a != filled(x + 1, y) {
+                    return None;
+                }
+            }
+        }
+        let mut outside = HashSet::from([(-1, -1)]);
+        let mut todo = vec![(-1, -1)];
+        while let Some((x, y)) = todo.pop() {
+            for (nx, ny) in [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)] {
+                let on_board = (-1..=size).contains(&nx) && (-1..=size).contains(&ny);
+                if on_board && !filled(nx, ny) && outside.insert((nx, ny)) {
+                    todo.push((nx, ny));
+                }
+            }
+        }
+        if outside.len() + blob.len() != ((size + 2) * (size + 2)) as usize {
+            return None;
+        }
+
+        // the sides of the squares, each going around its square the same
+        // way, so that the sides two squares share cancel out
+        let mut sides = HashSet::new();
+        for &(x, y) in blob.iter() {
+            let corners = [(x, y), (x + 1, y), (x + 1, y + 1), (x, y + 1)];
+            for i in 0..4 {
+                let (from, to) = (corners[i], corners[(i + 1) % 4]);
+                if !sides.remove(&(to, from)) {
+                    sides.insert((from, to));
+                }
+            }
+        }
+        let next: HashMap<_, _> = sides.into_iter().collect();
+        let start = *next.keys().min().unwrap();
+        let mut corners = vec![start];
+        while next[corners.last().unwrap()] != start {
+            corners.push(next[corners.last().unwrap()]);
+        }
+
+        let mut spread = || {
+            let mut at = 0;
+            (0..=size)
+                .map(|_| {
+                    at += 1 + rng.below(2) as i64;
+                    at
+                })
+                .collect::<Vec<i64>>()
+        };
+        let (xs, ys) = (spread(), spread());
+        // keep only the corners where the outline turns
+        let n = corners.len();
+        let turns = (0..n).filter(|&i| {
+            let (prev, at, next) = (corners[(i + n - 1) % n], corners[i], corners[(i + 1) % n]);
+            (prev.0 == at.0) != (at.0 == next.0)
+        });
+        Some(
+            turns
+                .map(|i| Point2::new(xs[corners[i].0 as usize], ys[corners[i].1 as usize]))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_axis() {
+        let axis = Axis::new([8, 3, 8, 1_000_000_000]);
+        assert_eq!(axis.values(), &[3, 8, 1_000_000_000]);
+        assert_eq!(axis.cells(), 7);
+        let cells: Vec<usize> = [-5, 3, 4, 7, 8, 9, 1_000_000_000, i64::MAX]
+            .into_iter()
+            .map(|v| axis.cell(v))
+            .collect();
+        assert_eq!(cells, vec![0, 1, 2, 2, 3, 4, 5, 6]);
+        assert!((0..7).all(|i| axis.has_integers(i)));
+
+        let axis = Axis::new([3, 4, 6]);
+        let holding: Vec<bool> = (0..7).map(|i| axis.has_integers(i)).collect();
+        assert_eq!(holding, [true, true, false, true, true, true, true]);
+    }
+
+    #[test]
+    fn test_prefix_sums() {
+        let grid = Grid::parse("1.3\n.5.\n7.9\n", ".13579").unwrap();
+        let sums = PrefixSums::new(&grid, |c| c.to_digit(10).unwrap_or(0) as i64);
+        assert_eq!(sums.sum((0, 0), (2, 2)), 25);
+        assert_eq!(sums.sum((1, 1), (1, 1)), 5);
+        assert_eq!(sums.sum((1, 0), (2, 1)), 8);
+        assert_eq!(sums.sum((0, 2), (2, 2)), 16);
+    }
+
+    #[test]
+    fn test_same_as_polygon() {
+        let shapes: [&[(i64, i64)]; 4] = [
+            &[
+                (7, 1),
+                (11, 1),
+                (11, 7),
+                (9, 7),
+                (9, 5),
+                (2, 5),
+                (2, 3),
+                (7, 3),
+            ],
+            &[
+                (0, 0),
+                (6, 0),
+                (6, 5),
+                (4, 5),
+                (4, 2),
+                (2, 2),
+                (2, 5),
+                (0, 5),
+            ],
+            &[
+                (0, 0),
+                (7, 0),
+                (7, 4),
+                (5, 4),
+                (5, 1),
+                (4, 1),
+                (4, 4),
+                (3, 4),
+                (3, 2),
+                (1, 2),
+                (1, 4),
+                (0, 4),
+            ],
+            // a notch as wide as one tile, leaving no tile outside
+            &[
+                (13, 10),
+                (15, 10),
+                (15, 11),
+                (16, 11),
+                (16, 12),
+                (15, 12),
+                (15, 13),
+                (13, 13),
+                (13, 12),
+                (14, 12),
+                (14, 11),
+                (13, 11),
+            ],
+        ];
+        for shape in shapes {
+            let vertices = shape.iter().map(|&(x, y)| Point2::new(x, y)).collect();
+            let poly = RectilinearPolygon::new(vertices).unwrap();
+            let compressed = CompressedPolygon::new(&poly);
+
+            let points: Vec<Point> = (-1..=17)
+                .flat_map(|x| (-1..=14).map(move |y| Point2::new(x, y)))
+                .collect();
+            for p1 in points.iter() {
+                assert_eq!(compressed.contains_point(p1), poly.contains_point(p1));
+                for p2 in points.iter() {
+                    let r = BoundingBox::<Point>::from_corners(p1, p2);
+                    assert_eq!(compressed.contains_rect(&r), poly.contains_rect(&r));
+                    assert_eq!(poly.contains_rect(&r), tiles_inside(&poly, &r));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_random_loops() {
+        let mut rng = XorShift::new(0xda94_2042_e4dd_58b5);
+        let mut loops = 0;
+        while loops < 300 {
+            let Some(vertices) = random_loop(&mut rng, 5) else {
+                continue;
+            };
+            loops += 1;
+            let poly = RectilinearPolygon::new(vertices.clone()).unwrap();
+            let compressed = CompressedPolygon::new(&poly);
+            for p1 in vertices.iter() {
+                for p2 in vertices.iter() {
+                    let r = BoundingBox::<Point>::from_corners(p1, p2);
+                    assert_eq!(
+                        compressed.contains_rect(&r),
+                        tiles_inside(&poly, &r),
+                        "{:?} {} {}",
+                        vertices,
+                        r.min,
+                        r.max
+                    );
+                }
+            }
+        }
+    }
+}
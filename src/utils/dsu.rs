@@ -0,0 +1,131 @@
+/// Disjoint sets over the elements `0..len`, merged with union by size and
+/// searched with path compression.
+#[derive(Debug, Clone)]
+pub struct UnionFind {
+    parent: Vec<usize>,
+    /// Only meaningful for roots.
+    size: Vec<usize>,
+    components: usize,
+}
+
+impl UnionFind {
+    /// `len` elements, each in its own component.
+    pub fn new(len: usize) -> Self {
+        UnionFind {
+            parent: (0..len).collect(),
+            size: vec![1; len],
+            components: len,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+
+    /// The representative of the component of `x`.
+    pub fn find(&mut self, x: usize) -> usize {
+        let root = self.root(x);
+        // point the whole path straight at the root
+        let mut x = x;
+        while self.parent[x] != root {
+            x = std::mem::replace(&mut self.parent[x], root);
+        }
+        root
+    }
+
+    /// Same as [`UnionFind::find`], without shortening the path.
+    fn root(&self, mut x: usize) -> usize {
+        while self.parent[x] != x {
+            x = self.parent[x];
+        }
+        x
+    }
+
+    /// Merges the components of `a` and `b`, and returns `false` if they
+    /// already were the same.
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+        if self.size[a] < self.size[b] {
+            std::mem::swap(&mut a, &mut b);
+        }
+        self.parent[b] = a;
+        self.size[a] += self.size[b];
+        self.components -= 1;
+        true
+    }
+
+    #[allow(dead_code)]
+    pub fn same(&mut self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+
+    pub fn component_count(&self) -> usize {
+        self.components
+    }
+
+    /// The number of elements in the component of `x`.
+    #[allow(dead_code)]
+    pub fn component_size(&mut self, x: usize) -> usize {
+        let root = self.find(x);
+        self.size[root]
+    }
+
+    /// The elements of each component, in increasing order of their smallest
+    /// element.
+    pub fn components(&self) -> Vec<Vec<usize>> {
+        let mut index = vec![usize::MAX; self.len()];
+        let mut components: Vec<Vec<usize>> = Vec::with_capacity(self.components);
+        for x in 0..self.len() {
+            let root = self.root(x);
+            if index[root] == usize::MAX {
+                index[root] = components.len();
+                components.push(Vec::with_capacity(self.size[root]));
+            }
+            components[index[root]].push(x);
+        }
+        components
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::UnionFind;
+
+    #[test]
+    fn test_union() {
+        let mut uf = UnionFind::new(6);
+        assert_eq!(uf.component_count(), 6);
+
+        assert!(uf.union(0, 1));
+        assert!(uf.union(2, 3));
+        assert!(uf.union(1, 3));
+        assert!(!uf.union(0, 2));
+        assert!(uf.same(0, 3));
+        assert!(!uf.same(0, 4));
+
+        assert_eq!(uf.component_count(), 3);
+        assert_eq!(uf.component_size(2), 4);
+        assert_eq!(uf.component_size(5), 1);
+        assert_eq!(uf.components(), vec![vec![0, 1, 2, 3], vec![4], vec![5]]);
+    }
+
+    #[test]
+    fn test_long_chain() {
+        let n = 10_000;
+        let mut uf = UnionFind::new(n);
+        for x in 1..n {
+            uf.union(x - 1, x);
+        }
+        assert_eq!(uf.component_count(), 1);
+        assert_eq!(uf.component_size(n - 1), n);
+        assert_eq!(uf.find(0), uf.find(n - 1));
+    }
+}
@@ -0,0 +1,318 @@
+use std::{
+    fmt,
+    ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign},
+    str::FromStr,
+};
+
+use super::parse::{pair, triple, ParseError};
+
+/// What a point coordinate needs to support: plain integer arithmetic,
+/// signed or not.
+pub trait Coord: Copy + Ord + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> {}
+
+impl<T> Coord for T where T: Copy + Ord + Add<Output = T> + Sub<Output = T> + Mul<Output = T> {}
+
+/// `|a - b|`, without going below zero for unsigned types.
+fn abs_diff<T: Coord>(a: T, b: T) -> T {
+    if a > b {
+        a - b
+    } else {
+        b - a
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Point2<T> {
+    pub x: T,
+    pub y: T,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Point3<T> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+impl<T> Point2<T> {
+    pub const fn new(x: T, y: T) -> Self {
+        Point2 { x, y }
+    }
+}
+
+impl<T> Point3<T> {
+    pub const fn new(x: T, y: T, z: T) -> Self {
+        Point3 { x, y, z }
+    }
+}
+
+impl<T: Coord> Point2<T> {
+    pub fn manhattan(&self, other: &Self) -> T {
+        abs_diff(self.x, other.x) + abs_diff(self.y, other.y)
+    }
+
+    pub fn chebyshev(&self, other: &Self) -> T {
+        abs_diff(self.x, other.x).max(abs_diff(self.y, other.y))
+    }
+
+    /// The squared euclidean distance, which orders points like the real one.
+    pub fn dist_sq(&self, other: &Self) -> T {
+        let (dx, dy) = (abs_diff(self.x, other.x), abs_diff(self.y, other.y));
+        dx * dx + dy * dy
+    }
+
+    /// The smallest coordinates of both points, axis by axis.
+    pub fn component_min(&self, other: &Self) -> Self {
+        Point2::new(self.x.min(other.x), self.y.min(other.y))
+    }
+
+    /// The largest coordinates of both points, axis by axis.
+    pub fn component_max(&self, other: &Self) -> Self {
+        Point2::new(self.x.max(other.x), self.y.max(other.y))
+    }
+}
+
+impl<T: Coord> Point3<T> {
+    pub fn manhattan(&self, other: &Self) -> T {
+        abs_diff(self.x, other.x) + abs_diff(self.y, other.y) + abs_diff(self.z, other.z)
+    }
+
+    pub fn chebyshev(&self, other: &Self) -> T {
+        abs_diff(self.x, other.x)
+            .max(abs_diff(self.y, other.y))
+            .max(abs_diff(self.z, other.z))
+    }
+
+    /// The squared euclidean distance, which orders points like the real one.
+    pub fn dist_sq(&self, other: &Self) -> T {
+        let dx = abs_diff(self.x, other.x);
+        let dy = abs_diff(self.y, other.y);
+        let dz = abs_diff(self.z, other.z);
+        dx * dx + dy * dy + dz * dz
+    }
+
+    /// The smallest coordinates of both points, axis by axis.
+    pub fn component_min(&self, other: &Self) -> Self {
+        Point3::new(
+            self.x.min(other.x),
+            self.y.min(other.y),
+            self.z.min(other.z),
+        )
+    }
+
+    /// The largest coordinates of both points, axis by axis.
+    pub fn component_max(&self, other: &Self) -> Self {
+        Point3::new(
+            self.x.max(other.x),
+            self.y.max(other.y),
+            self.z.max(other.z),
+        )
+    }
+}
+
+macro_rules! impl_ops {
+    ($point:ident { $($c:ident),+ }) => {
+        impl<T: Add<Output = T>> Add for $point<T> {
+            type Output = Self;
+            fn add(self, rhs: Self) -> Self {
+                $point { $($c: self.$c + rhs.$c),+ }
+            }
+        }
+
+        impl<T: Sub<Output = T>> Sub for $point<T> {
+            type Output = Self;
+            fn sub(self, rhs: Self) -> Self {
+                $point { $($c: self.$c - rhs.$c),+ }
+            }
+        }
+
+        impl<T: Copy + Mul<Output = T>> Mul<T> for $point<T> {
+            type Output = Self;
+            fn mul(self, rhs: T) -> Self {
+                $point { $($c: self.$c * rhs),+ }
+            }
+        }
+
+        impl<T: Neg<Output = T>> Neg for $point<T> {
+            type Output = Self;
+            fn neg(self) -> Self {
+                $point { $($c: -self.$c),+ }
+            }
+        }
+
+        impl<T: Copy + Add<Output = T>> AddAssign for $point<T> {
+            fn add_assign(&mut self, rhs: Self) {
+                *self = *self + rhs;
+            }
+        }
+
+        impl<T: Copy + Sub<Output = T>> SubAssign for $point<T> {
+            fn sub_assign(&mut self, rhs: Self) {
+                *self = *self - rhs;
+            }
+        }
+    };
+}
+
+impl_ops!(Point2 { x, y });
+impl_ops!(Point3 { x, y, z });
+
+impl<T> From<Point2<T>> for [T; 2] {
+    fn from(p: Point2<T>) -> Self {
+        [p.x, p.y]
+    }
+}
+
+impl<T> From<Point3<T>> for [T; 3] {
+    fn from(p: Point3<T>) -> Self {
+        [p.x, p.y, p.z]
+    }
+}
+
+/// Parses `x,y`.
+impl<T: FromStr> FromStr for Point2<T> {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (x, y) = pair(s, ',')?;
+        Ok(Point2 { x, y })
+    }
+}
+
+/// Parses `x,y,z`.
+impl<T: FromStr> FromStr for Point3<T> {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (x, y, z) = triple(s, ',')?;
+        Ok(Point3 { x, y, z })
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Point2<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", self.x, self.y)
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Point3<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{},{}", self.x, self.y, self.z)
+    }
+}
+
+/// The smallest box containing some points, bounds included.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BoundingBox<P> {
+    pub min: P,
+    pub max: P,
+}
+
+macro_rules! impl_bounding_box {
+    ($point:ident { $($c:ident),+ }) => {
+        // not every dimension needs every method
+        #[allow(dead_code)]
+        impl<T: Coord> BoundingBox<$point<T>> {
+            /// The box with `p1` and `p2` as opposite corners.
+            pub fn from_corners(p1: &$point<T>, p2: &$point<T>) -> Self {
+                BoundingBox {
+                    min: p1.component_min(p2),
+                    max: p1.component_max(p2),
+                }
+            }
+
+            /// The bounding box of `points`, or `None` when there are none.
+            pub fn from_points<'a>(points: impl IntoIterator<Item = &'a $point<T>>) -> Option<Self>
+            where
+                T: 'a,
+            {
+                let mut points = points.into_iter();
+                let first = points.next()?;
+                Some(points.fold(BoundingBox { min: *first, max: *first }, |bb, p| {
+                    BoundingBox {
+                        min: bb.min.component_min(p),
+                        max: bb.max.component_max(p),
+                    }
+                }))
+            }
+
+            pub fn contains(&self, p: &$point<T>) -> bool {
+                $(self.min.$c <= p.$c && p.$c <= self.max.$c)&&+
+            }
+
+            /// `max - min` along each axis.
+            pub fn extent(&self) -> $point<T> {
+                self.max - self.min
+            }
+        }
+    };
+}
+
+impl_bounding_box!(Point2 { x, y });
+impl_bounding_box!(Point3 { x, y, z });
+
+#[cfg(test)]
+mod test {
+    use super::{BoundingBox, Point2, Point3};
+
+    #[test]
+    fn test_ops() {
+        let a = Point2::new(7, 1);
+        let b = Point2::new(2, 5);
+        assert_eq!(a + b, Point2::new(9, 6));
+        assert_eq!(a - b, Point2::new(5, -4));
+        assert_eq!(a * 3, Point2::new(21, 3));
+        assert_eq!(-a, Point2::new(-7, -1));
+
+        let mut c = Point3::new(1, 2, 3);
+        c += Point3::new(1, 1, 1);
+        c -= Point3::new(0, 0, 4);
+        assert_eq!(c, Point3::new(2, 3, 0));
+    }
+
+    #[test]
+    fn test_distances() {
+        // unsigned coordinates must not underflow
+        let a: Point2<u64> = Point2::new(7, 1);
+        let b: Point2<u64> = Point2::new(2, 5);
+        assert_eq!(a.manhattan(&b), 9);
+        assert_eq!(b.chebyshev(&a), 5);
+        assert_eq!(a.dist_sq(&b), 41);
+
+        let p: Point3<i64> = Point3::new(162, 817, 812);
+        let q: Point3<i64> = Point3::new(425, 690, 689);
+        assert_eq!(p.dist_sq(&q), 263 * 263 + 127 * 127 + 123 * 123);
+        assert_eq!(p.manhattan(&q), 263 + 127 + 123);
+        assert_eq!(q.chebyshev(&p), 263);
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("7,1".parse(), Ok(Point2::<u64>::new(7, 1)));
+        assert_eq!(" -3, 4 ".parse(), Ok(Point2::<i32>::new(-3, 4)));
+        assert_eq!("162,817,812".parse(), Ok(Point3::<i64>::new(162, 817, 812)));
+        assert!("162,817".parse::<Point3<i64>>().is_err());
+        assert!("7;1".parse::<Point2<u64>>().is_err());
+        assert_eq!(Point3::new(1, 2, 3).to_string(), "1,2,3");
+    }
+
+    #[test]
+    fn test_bounding_box() {
+        let points: Vec<Point2<u64>> =
+            vec![Point2::new(7, 1), Point2::new(11, 7), Point2::new(2, 5)];
+        let bb = BoundingBox::<Point2<u64>>::from_points(&points).unwrap();
+        assert_eq!(bb.min, Point2::new(2, 1));
+        assert_eq!(bb.max, Point2::new(11, 7));
+        assert_eq!(bb.extent(), Point2::new(9, 6));
+        assert!(bb.contains(&Point2::new(2, 7)));
+        assert!(!bb.contains(&Point2::new(1, 7)));
+        assert_eq!(
+            BoundingBox::<Point2<u64>>::from_corners(&points[1], &points[2]),
+            BoundingBox {
+                min: Point2::new(2, 5),
+                max: Point2::new(11, 7)
+            }
+        );
+        assert_eq!(BoundingBox::<Point3<i64>>::from_points(&[]), None);
+    }
+}
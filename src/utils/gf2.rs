@@ -0,0 +1,268 @@
+//! Linear algebra over GF(2), where adding is xor-ing.
+
+use std::{fmt, ops::BitXorAssign};
+
+/// A fixed-length vector of bits, packed 64 to a word.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BitVec {
+    len: usize,
+    words: Vec<u64>,
+}
+
+impl BitVec {
+    /// `len` zeros.
+    pub fn new(len: usize) -> Self {
+        BitVec {
+            len,
+            words: vec![0; len.div_ceil(64)],
+        }
+    }
+
+    /// The `len` low bits of `mask`, bit `i` first.
+    pub fn from_mask(mask: u64, len: usize) -> Self {
+        (0..len).map(|i| mask >> i & 1 == 1).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, i: usize) -> bool {
+        assert!(i < self.len, "bit {} out of {}", i, self.len);
+        self.words[i / 64] >> (i % 64) & 1 == 1
+    }
+
+    pub fn set(&mut self, i: usize, value: bool) {
+        assert!(i < self.len, "bit {} out of {}", i, self.len);
+        if value {
+            self.words[i / 64] |= 1 << (i % 64);
+        } else {
+            self.words[i / 64] &= !(1 << (i % 64));
+        }
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// The indices of the set bits, in increasing order.
+    pub fn ones(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.len).filter(|&i| self.get(i))
+    }
+}
+
+impl BitXorAssign<&BitVec> for BitVec {
+    fn bitxor_assign(&mut self, rhs: &BitVec) {
+        assert_eq!(self.len, rhs.len, "xor of bit vectors of different lengths");
+        for (a, b) in self.words.iter_mut().zip(&rhs.words) {
+            *a ^= b;
+        }
+    }
+}
+
+impl FromIterator<bool> for BitVec {
+    fn from_iter<I: IntoIterator<Item = bool>>(iter: I) -> Self {
+        let mut bits = BitVec::new(0);
+        for bit in iter {
+            if bits.len.is_multiple_of(64) {
+                bits.words.push(0);
+            }
+            bits.len += 1;
+            bits.set(bits.len - 1, bit);
+        }
+        bits
+    }
+}
+
+/// Bit `i` first, like `0110`.
+impl fmt::Display for BitVec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for i in 0..self.len {
+            write!(f, "{}", self.get(i) as u8)?;
+        }
+        Ok(())
+    }
+}
+
+/// The solutions of a linear system: one of them, plus any combination of
+/// the null space basis.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Solutions {
+    particular: BitVec,
+    null_space: Vec<BitVec>,
+}
+
+/// Solves `a x = b`, where `a` is given by its rows, each as long as `x`.
+/// Returns `None` if there is no solution.
+pub fn solve(a: &[BitVec], b: &BitVec) -> Option<Solutions> {
+    assert_eq!(a.len(), b.len(), "one right-hand side bit per row");
+    let cols = a.first().map_or(0, |row| row.len());
+
+    // augmented rows: the equation's coefficients, then its right-hand side
+    let mut rows: Vec<BitVec> = a
+        .iter()
+        .enumerate()
+        .map(|(i, row)| (0..cols).map(|c| row.get(c)).chain([b.get(i)]).collect())
+        .collect();
+
+    // reduced row echelon form, remembering each pivot's column
+    let mut pivots: Vec<usize> = Vec::new();
+    for col in 0..cols {
+        let r = pivots.len();
+        let Some(found) = (r..rows.len()).find(|&i| rows[i].get(col)) else {
+            continue;
+        };
+        rows.swap(r, found);
+        let pivot = rows[r].clone();
+        for (i, row) in rows.iter_mut().enumerate() {
+            if i != r && row.get(col) {
+                *row ^= &pivot;
+            }
+        }
+        pivots.push(col);
+    }
+
+    // left over rows read 0 = rhs
+    if rows[pivots.len()..].iter().any(|row| row.get(cols)) {
+        return None;
+    }
+
+    let mut particular = BitVec::new(cols);
+    for (r, &col) in pivots.iter().enumerate() {
+        particular.set(col, rows[r].get(cols));
+    }
+
+    let free = (0..cols).filter(|c| !pivots.contains(c));
+    let null_space = free
+        .map(|f| {
+            let mut v = BitVec::new(cols);
+            v.set(f, true);
+            for (r, &col) in pivots.iter().enumerate() {
+                v.set(col, rows[r].get(f));
+            }
+            v
+        })
+        .collect();
+
+    Some(Solutions {
+        particular,
+        null_space,
+    })
+}
+
+impl Solutions {
+    #[allow(dead_code)]
+    pub fn particular(&self) -> &BitVec {
+        &self.particular
+    }
+
+    #[allow(dead_code)]
+    pub fn null_space(&self) -> &[BitVec] {
+        &self.null_space
+    }
+
+    /// Every solution, `2^k` of them for a null space of dimension `k`,
+    /// which must be below 64 for them to be counted.
+    pub fn iter(&self) -> impl Iterator<Item = BitVec> + '_ {
+        let dimension = self.null_space.len();
+        assert!(dimension < 64, "too many solutions to try: 2^{dimension}");
+        let count = 1u64 << dimension;
+        let mut current = self.particular.clone();
+        // Gray code order: each solution differs from the previous one by a
+        // single basis vector
+        (0..count).map(move |i| {
+            if i > 0 {
+                current ^= &self.null_space[i.trailing_zeros() as usize];
+            }
+            current.clone()
+        })
+    }
+
+    /// A solution with as few ones as possible, found by trying them all.
+    pub fn min_weight(&self) -> BitVec {
+        self.iter().min_by_key(|x| x.count_ones()).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{solve, BitVec};
+    use crate::utils::rng::XorShift;
+
+    /// The rows of the system where column `j` is `columns[j]`.
+    fn rows(columns: &[u64], len: usize) -> Vec<BitVec> {
+        (0..len)
+            .map(|i| columns.iter().map(|c| c >> i & 1 == 1).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_bitvec() {
+        let mut bits = BitVec::from_mask(0b0110, 4);
+        assert_eq!(bits.to_string(), "0110");
+        bits.set(3, true);
+        bits ^= &BitVec::from_mask(0b0011, 4);
+        assert_eq!(bits.to_string(), "1011");
+        assert_eq!(bits.ones().collect::<Vec<_>>(), vec![0, 2, 3]);
+
+        let long: BitVec = (0..130).map(|i| i % 3 == 0).collect();
+        assert_eq!(long.count_ones(), 44);
+        assert!(long.get(129) && !long.get(128));
+    }
+
+    #[test]
+    fn test_solve() {
+        // [.##.] (3) (1,3) (2) (2,3) (0,2) (0,1)
+        let a = rows(&[0b1000, 0b1010, 0b0100, 0b1100, 0b0101, 0b0011], 4);
+        let b = BitVec::from_mask(0b0110, 4);
+        let solutions = solve(&a, &b).unwrap();
+        assert_eq!(solutions.null_space().len(), 2);
+        assert_eq!(solutions.iter().count(), 4);
+        assert_eq!(solutions.min_weight().count_ones(), 2);
+
+        // light 2 is never toggled
+        let a = rows(&[0b001, 0b010, 0b011], 3);
+        assert_eq!(solve(&a, &BitVec::from_mask(0b100, 3)), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "too many solutions to try: 2^64")]
+    fn test_too_many_solutions() {
+        // a single light that no button toggles
+        let solutions = solve(&rows(&[0; 63], 1), &BitVec::new(1)).unwrap();
+        assert_eq!(solutions.iter().next(), Some(BitVec::new(63)));
+
+        let solutions = solve(&rows(&[0; 64], 1), &BitVec::new(1)).unwrap();
+        assert_eq!(solutions.null_space().len(), 64);
+        solutions.min_weight();
+    }
+
+    #[test]
+    fn test_min_weight_brute_force() {
+        let mut rng = XorShift::new(0x9e37_79b9_7f4a_7c15);
+
+        for _ in 0..200 {
+            let (len, n) = (6, 1 + rng.below(8) as usize);
+            let columns: Vec<u64> = (0..n).map(|_| rng.below(64)).collect();
+            let target = rng.below(64);
+
+            let best = (0..1u64 << n)
+                .filter(|choice| {
+                    let reached = (0..n)
+                        .filter(|j| choice >> j & 1 == 1)
+                        .fold(0, |acc, j| acc ^ columns[j]);
+                    reached == target
+                })
+                .map(|choice| choice.count_ones() as usize)
+                .min();
+
+            let solutions = solve(&rows(&columns, len), &BitVec::from_mask(target, len));
+            assert_eq!(solutions.map(|s| s.min_weight().count_ones()), best);
+        }
+    }
+}
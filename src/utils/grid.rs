@@ -0,0 +1,240 @@
+use std::fmt;
+
+use super::parse::{char_grid, ParseError};
+
+/// A position in a grid, as `(x, y)` with `y` growing downwards.
+pub type Pos = (usize, usize);
+
+const DIRS_4: [(isize, isize); 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+const DIRS_8: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+/// A rectangular map of cells, stored row by row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Self
+    where
+        T: Clone,
+    {
+        Grid {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, (x, y): Pos) -> bool {
+        x < self.width && y < self.height
+    }
+
+    pub fn get(&self, pos: Pos) -> Option<&T> {
+        self.contains(pos)
+            .then(|| &self.cells[pos.1 * self.width + pos.0])
+    }
+
+    pub fn get_mut(&mut self, pos: Pos) -> Option<&mut T> {
+        self.contains(pos)
+            .then(|| &mut self.cells[pos.1 * self.width + pos.0])
+    }
+
+    /// Replaces the cell at `pos` and returns the previous value, or `None`
+    /// if `pos` is out of the grid.
+    pub fn set(&mut self, pos: Pos, value: T) -> Option<T> {
+        self.get_mut(pos).map(|cell| std::mem::replace(cell, value))
+    }
+
+    fn offsets<'a>(
+        &self,
+        (x, y): Pos,
+        dirs: &'a [(isize, isize)],
+    ) -> impl Iterator<Item = Pos> + 'a {
+        let (width, height) = (self.width, self.height);
+        dirs.iter().filter_map(move |(dx, dy)| {
+            let nx = x.checked_add_signed(*dx)?;
+            let ny = y.checked_add_signed(*dy)?;
+            (nx < width && ny < height).then_some((nx, ny))
+        })
+    }
+
+    /// The up to 4 orthogonal neighbours of `pos` that are in the grid.
+    pub fn neighbours_4(&self, pos: Pos) -> impl Iterator<Item = Pos> {
+        self.offsets(pos, &DIRS_4)
+    }
+
+    /// The up to 8 orthogonal and diagonal neighbours of `pos` that are in the grid.
+    pub fn neighbours_8(&self, pos: Pos) -> impl Iterator<Item = Pos> {
+        self.offsets(pos, &DIRS_8)
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks(self.width.max(1))
+    }
+
+    #[allow(dead_code)]
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        self.cells.iter().skip(x).step_by(self.width.max(1))
+    }
+
+    #[allow(dead_code)]
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(|x| self.column(x))
+    }
+
+    /// Every cell along with its position, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = (Pos, &T)> {
+        let width = self.width;
+        self.cells
+            .iter()
+            .enumerate()
+            .map(move |(i, cell)| ((i % width, i / width), cell))
+    }
+
+    /// The positions of the cells matching `pred`, row by row.
+    pub fn positions<'a>(
+        &'a self,
+        pred: impl Fn(&T) -> bool + 'a,
+    ) -> impl Iterator<Item = Pos> + 'a {
+        self.iter()
+            .filter_map(move |(pos, cell)| pred(cell).then_some(pos))
+    }
+
+    pub fn map<U>(&self, f: impl Fn(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+
+    /// Draws the grid as text, one character per cell and one line per row.
+    pub fn render_with(&self, f: impl Fn(&T) -> char) -> String {
+        let mut text = String::with_capacity((self.width + 1) * self.height);
+        for row in self.rows() {
+            text.extend(row.iter().map(&f));
+            text.push('\n');
+        }
+        text
+    }
+}
+
+impl Grid<char> {
+    /// Reads a character map, whose lines must all have the same length and
+    /// only use characters among `allowed`.
+    pub fn parse(input: &str, allowed: &str) -> Result<Self, ParseError> {
+        let lines = char_grid(input, allowed)?;
+        let width = lines.first().map_or(0, |line| line.len());
+        if let Some((line, _)) = input
+            .lines()
+            .zip(lines.iter())
+            .find(|(_, chars)| chars.len() != width)
+        {
+            return Err(ParseError::new(line, format!("{} characters", width)));
+        }
+
+        Ok(Grid {
+            width,
+            height: lines.len(),
+            cells: lines.into_iter().flatten().collect(),
+        })
+    }
+
+    /// The position of the first `c`, row by row.
+    #[allow(dead_code)]
+    pub fn find(&self, c: char) -> Option<Pos> {
+        self.positions(|cell| *cell == c).next()
+    }
+
+    pub fn render(&self) -> String {
+        self.render_with(|c| *c)
+    }
+}
+
+impl fmt::Display for Grid<char> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.render())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Grid;
+
+    const MAP: &str = "..@\n@S.\n.@@\n";
+
+    #[test]
+    fn test_parse() {
+        let grid = Grid::parse(MAP, ".@S").unwrap();
+        assert_eq!((grid.width(), grid.height()), (3, 3));
+        assert_eq!(grid.get((2, 0)), Some(&'@'));
+        assert_eq!(grid.get((3, 0)), None);
+        assert_eq!(grid.find('S'), Some((1, 1)));
+        assert_eq!(grid.to_string(), MAP);
+
+        let err = Grid::parse("...\n..\n...\n", ".").unwrap_err();
+        assert_eq!(
+            (err.found.as_str(), err.expected.as_str()),
+            ("..", "3 characters")
+        );
+        assert!(Grid::parse(MAP, ".@").is_err());
+    }
+
+    #[test]
+    fn test_set() {
+        let mut grid = Grid::new(2, 2, false);
+        assert_eq!(grid.set((1, 0), true), Some(false));
+        assert_eq!(grid.set((2, 0), true), None);
+        assert_eq!(grid.render_with(|b| if *b { '#' } else { '.' }), ".#\n..\n");
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let grid = Grid::parse(MAP, ".@S").unwrap();
+        let mut n4: Vec<_> = grid.neighbours_4((0, 0)).collect();
+        n4.sort();
+        assert_eq!(n4, vec![(0, 1), (1, 0)]);
+        assert_eq!(grid.neighbours_8((1, 1)).count(), 8);
+        assert_eq!(grid.neighbours_8((2, 2)).count(), 3);
+        let rolls = grid
+            .neighbours_8((1, 1))
+            .filter(|pos| grid.get(*pos) == Some(&'@'))
+            .count();
+        assert_eq!(rolls, 4);
+    }
+
+    #[test]
+    fn test_rows_and_columns() {
+        let grid = Grid::parse(MAP, ".@S").unwrap();
+        let rows: Vec<String> = grid.rows().map(|r| r.iter().collect()).collect();
+        assert_eq!(rows, vec!["..@", "@S.", ".@@"]);
+        let cols: Vec<String> = grid.columns().map(|c| c.collect()).collect();
+        assert_eq!(cols, vec![".@.", ".S@", "@.@"]);
+        assert_eq!(grid.row(1), &['@', 'S', '.']);
+        assert_eq!(grid.positions(|c| *c == '@').count(), 4);
+    }
+}
@@ -0,0 +1,509 @@
+//! Exact integer programs of the form: minimize `Σx` subject to `Ax = b`,
+//! `x ≥ 0` integer.
+//!
+//! The system is brought to reduced row echelon form without leaving the
+//! integers, which expresses every pivot variable in terms of the free ones.
+//! The free variables are then given values one at a time, within the bounds
+//! that `b` puts on them. A partial assignment is dropped as soon as some
+//! pivot can no longer end up within its bounds, or as soon as the linear
+//! relaxation of the rest, solved exactly over the rationals by the simplex
+//! method on its dual, shows it can't beat the best sum found so far. Values
+//! are tried going outwards from the relaxed optimum, and each direction
+//! stops at the first value the relaxation rules out.
+
+use std::{
+    cmp::Ordering,
+    ops::{Div, Mul, Sub},
+};
+
+fn gcd(a: i128, b: i128) -> i128 {
+    if b == 0 {
+        a.abs()
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Divides `row` by the gcd of its entries, and makes its first non-zero
+/// entry positive.
+fn normalize(row: &mut [i128]) {
+    let g = row.iter().fold(0, |g, &v| gcd(g, v));
+    let sign = row.iter().find(|&&v| v != 0).map_or(1, |v| v.signum());
+    if g > 1 || sign < 0 {
+        row.iter_mut().for_each(|v| *v /= g * sign);
+    }
+}
+
+/// A fraction in lowest terms, with a positive denominator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Ratio {
+    num: i128,
+    den: i128,
+}
+
+impl Ratio {
+    fn new(num: i128, den: i128) -> Self {
+        let g = gcd(num, den) * den.signum();
+        Ratio {
+            num: num / g,
+            den: den / g,
+        }
+    }
+
+    fn int(v: i128) -> Self {
+        Ratio { num: v, den: 1 }
+    }
+
+    /// The smallest integer at least as large.
+    fn ceil(self) -> i128 {
+        self.num.div_euclid(self.den) + (self.num.rem_euclid(self.den) != 0) as i128
+    }
+}
+
+impl Sub for Ratio {
+    type Output = Ratio;
+    fn sub(self, o: Ratio) -> Ratio {
+        Ratio::new(self.num * o.den - o.num * self.den, self.den * o.den)
+    }
+}
+
+impl Mul for Ratio {
+    type Output = Ratio;
+    fn mul(self, o: Ratio) -> Ratio {
+        Ratio::new(self.num * o.num, self.den * o.den)
+    }
+}
+
+impl Div for Ratio {
+    type Output = Ratio;
+    fn div(self, o: Ratio) -> Ratio {
+        Ratio::new(self.num * o.den, self.den * o.num)
+    }
+}
+
+impl PartialOrd for Ratio {
+    fn partial_cmp(&self, o: &Ratio) -> Option<Ordering> {
+        Some(self.cmp(o))
+    }
+}
+
+impl Ord for Ratio {
+    fn cmp(&self, o: &Ratio) -> Ordering {
+        (self.num * o.den).cmp(&(o.num * self.den))
+    }
+}
+
+/// The smallest `Σ x_j` over the columns `a_j` of `vars` with
+/// `Σ x_j a_j = left` and `x ≥ 0`, not necessarily integer, along with the
+/// `x` reaching it, or `None` if there is no such `x`.
+///
+/// It is found as its dual, the largest `y · left` with `y · a_j ≤ 1` for
+/// every `j`, by the simplex method with Bland's rule. `y` is written as
+/// `y⁺ - y⁻` with both non-negative, and `y = 0` is where it starts. In the
+/// end, each `x_j` is the reduced cost of the slack of its constraint.
+fn relaxed_min(a: &[Vec<i64>], vars: &[usize], left: &[i128]) -> Option<(Ratio, Vec<Ratio>)> {
+    let (m, n) = (left.len(), vars.len());
+    let cols = 2 * m + n;
+    // one row per variable, over y⁺, y⁻, the slacks and the right-hand side
+    let zero = Ratio::int(0);
+    let mut rows: Vec<Vec<Ratio>> = vars
+        .iter()
+        .enumerate()
+        .map(|(r, &j)| {
+            let plus = a.iter().map(|row| Ratio::int(row[j] as i128));
+            let minus = a.iter().map(|row| Ratio::int(-row[j] as i128));
+            let slacks = (0..n).map(|s| Ratio::int((s == r) as i128));
+            plus.chain(minus)
+                .chain(slacks)
+                .chain([Ratio::int(1)])
+                .collect()
+        })
+        .collect();
+    let mut costs: Vec<Ratio> = left
+        .iter()
+        .map(|&v| Ratio::int(-v))
+        .chain(left.iter().map(|&v| Ratio::int(v)))
+        .chain((0..=n).map(|_| zero))
+        .collect();
+    let mut basis: Vec<usize> = (2 * m..cols).collect();
+
+    while let Some(col) = (0..cols).find(|&c| costs[c] < zero) {
+        let leave = (0..n)
+            .filter(|&r| rows[r][col] > zero)
+            .min_by_key(|&r| (rows[r][cols] / rows[r][col], basis[r]));
+        // y can grow without end: no x at all makes up `left`
+        let r = leave?;
+
+        let pivot = rows[r][col];
+        rows[r].iter_mut().for_each(|v| *v = *v / pivot);
+        let pivot_row = rows[r].clone();
+        for (i, row) in rows.iter_mut().enumerate() {
+            let factor = row[col];
+            if i != r && factor != zero {
+                row.iter_mut()
+                    .zip(&pivot_row)
+                    .for_each(|(v, &p)| *v = *v - factor * p);
+            }
+        }
+        let factor = costs[col];
+        costs
+            .iter_mut()
+            .zip(&pivot_row)
+            .for_each(|(v, &p)| *v = *v - factor * p);
+        basis[r] = col;
+    }
+    Some((costs[cols], costs[2 * m..cols].to_vec()))
+}
+
+/// A pivot variable, as `(rhs - Σ coefs[f] * x[f]) / pivot` over the free
+/// variables `f`.
+struct Pivot {
+    var: usize,
+    pivot: i128,
+    rhs: i128,
+    coefs: Vec<i128>,
+}
+
+struct Search<'a> {
+    a: &'a [Vec<i64>],
+    pivots: &'a [Pivot],
+    /// The free variables, in the order they are given values.
+    free: &'a [usize],
+    bounds: &'a [i128],
+    /// For each number `k` of free variables with a value, the variables
+    /// still without one.
+    unset: Vec<Vec<usize>>,
+    /// For each `k` and pivot, the least and the most the free variables
+    /// still without a value can take off its right-hand side.
+    spread: Vec<Vec<(i128, i128)>>,
+    /// The right-hand side of each row, less what the free variables with a
+    /// value add to it.
+    left: Vec<i128>,
+    /// The same for the right-hand side of each pivot.
+    pivot_left: Vec<i128>,
+    x: Vec<i128>,
+    best: Option<(i128, Vec<i128>)>,
+}
+
+impl Search<'_> {
+    /// Gives `value` to free variable `k`, or takes it back with `-value`.
+    fn assign(&mut self, k: usize, value: i128) {
+        let var = self.free[k];
+        self.x[var] += value;
+        for (left, row) in self.left.iter_mut().zip(self.a) {
+            *left -= row[var] as i128 * value;
+        }
+        for (left, p) in self.pivot_left.iter_mut().zip(self.pivots) {
+            *left -= p.coefs[k] * value;
+        }
+    }
+
+    /// Tries every value of the free variable `k` onwards, given a sum of
+    /// `partial` for the ones before it. Returns `false` if the relaxation
+    /// shows that no value gives a smaller sum than the best one so far.
+    fn run(&mut self, k: usize, partial: i128) -> bool {
+        // every pivot must still be able to end up within its bounds
+        let pivots = self.pivots.iter().zip(&self.pivot_left);
+        for ((p, &left), &(least, most)) in pivots.zip(&self.spread[k]) {
+            if left - least < 0 || left - most > p.pivot * self.bounds[p.var] {
+                return true;
+            }
+        }
+        let Some((relaxed, x)) = relaxed_min(self.a, &self.unset[k], &self.left) else {
+            return false;
+        };
+        let bound = partial + relaxed.ceil();
+        if self.best.as_ref().is_some_and(|(best, _)| bound >= *best) {
+            return false;
+        }
+
+        if k < self.free.len() {
+            let var = self.free[k];
+            // the rows bound the variable as they do the right-hand sides
+            let most = self
+                .a
+                .iter()
+                .zip(&self.left)
+                .filter(|(row, _)| row[var] > 0)
+                .map(|(row, &left)| left / row[var] as i128)
+                .fold(self.bounds[var], i128::min);
+            // The relaxed sum is convex in the value, and smallest at its
+            // value in `x`, the first of the variables without one. Going
+            // away from there, once a value is ruled out so are the next.
+            let start = x[0].ceil().min(most);
+            for value in start..=most {
+                self.assign(k, value);
+                let open = self.run(k + 1, partial + value);
+                self.assign(k, -value);
+                if !open {
+                    break;
+                }
+            }
+            for value in (0..start).rev() {
+                self.assign(k, value);
+                let open = self.run(k + 1, partial + value);
+                self.assign(k, -value);
+                if !open {
+                    break;
+                }
+            }
+            return true;
+        }
+
+        let mut total = partial;
+        for (p, &num) in self.pivots.iter().zip(&self.pivot_left) {
+            if num % p.pivot != 0 {
+                return true;
+            }
+            let value = num / p.pivot;
+            if value < 0 || value > self.bounds[p.var] {
+                return true;
+            }
+            self.x[p.var] = value;
+            total += value;
+        }
+        if self.best.as_ref().is_none_or(|(best, _)| total < *best) {
+            self.best = Some((total, self.x.clone()));
+        }
+        true
+    }
+}
+
+/// The non-negative integer `x` with the smallest sum such that `a x = b`,
+/// where `a` is given by its rows. Returns `None` if there is none.
+///
+/// The coefficients of `a` must not be negative, so that `b` bounds every
+/// variable.
+pub fn min_sum(a: &[Vec<i64>], b: &[i64]) -> Option<Vec<i64>> {
+    assert_eq!(a.len(), b.len(), "one right-hand side value per row");
+    let cols = a.first().map_or(0, |row| row.len());
+    assert!(
+        a.iter().flatten().all(|&c| c >= 0),
+        "coefficients must not be negative"
+    );
+    if b.iter().any(|&v| v < 0) {
+        return None;
+    }
+
+    // a variable can't exceed any right-hand side it contributes to, and one
+    // that contributes to nothing is best left at 0
+    let bounds: Vec<i128> = (0..cols)
+        .map(|j| {
+            a.iter()
+                .zip(b)
+                .filter(|(row, _)| row[j] > 0)
+                .map(|(row, &v)| (v / row[j]) as i128)
+                .min()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    // augmented rows, reduced to row echelon form without fractions
+    let mut rows: Vec<Vec<i128>> = a
+        .iter()
+        .zip(b)
+        .map(|(row, &v)| row.iter().chain([&v]).map(|&c| c as i128).collect())
+        .collect();
+    let mut pivot_cols: Vec<usize> = Vec::new();
+    for col in 0..cols {
+        let r = pivot_cols.len();
+        let Some(found) = (r..rows.len()).find(|&i| rows[i][col] != 0) else {
+            continue;
+        };
+        rows.swap(r, found);
+        normalize(&mut rows[r]);
+        let pivot = rows[r].clone();
+        for (i, row) in rows.iter_mut().enumerate() {
+            let factor = row[col];
+            if i != r && factor != 0 {
+                row.iter_mut()
+                    .zip(&pivot)
+                    .for_each(|(v, p)| *v = *v * pivot[col] - p * factor);
+                normalize(row);
+            }
+        }
+        pivot_cols.push(col);
+    }
+
+    // left over rows read 0 = rhs
+    if rows[pivot_cols.len()..].iter().any(|row| row[cols] != 0) {
+        return None;
+    }
+
+    // the free variables adding the most to the rows come first, which
+    // tightens the lower bounds on the rest sooner
+    let column_sum = |j: usize| a.iter().map(|row| row[j] as i128).sum::<i128>();
+    let mut free: Vec<usize> = (0..cols).filter(|c| !pivot_cols.contains(c)).collect();
+    free.sort_by_key(|&j| std::cmp::Reverse(column_sum(j)));
+    let pivots: Vec<Pivot> = pivot_cols
+        .iter()
+        .zip(&rows)
+        .map(|(&var, row)| Pivot {
+            var,
+            pivot: row[var],
+            rhs: row[cols],
+            coefs: free.iter().map(|&f| row[f]).collect(),
+        })
+        .collect();
+
+    let unset = (0..=free.len())
+        .map(|k| free[k..].iter().chain(&pivot_cols).copied().collect())
+        .collect();
+    let spread = (0..=free.len())
+        .map(|k| {
+            pivots
+                .iter()
+                .map(|p| {
+                    let takes = (k..free.len()).map(|f| p.coefs[f] * bounds[free[f]]);
+                    takes.fold((0, 0), |(least, most), t| {
+                        (least + t.min(0), most + t.max(0))
+                    })
+                })
+                .collect()
+        })
+        .collect();
+
+    let mut search = Search {
+        a,
+        pivots: &pivots,
+        free: &free,
+        bounds: &bounds,
+        unset,
+        spread,
+        left: b.iter().map(|&v| v as i128).collect(),
+        pivot_left: pivots.iter().map(|p| p.rhs).collect(),
+        x: vec![0; cols],
+        best: None,
+    };
+    search.run(0, 0);
+    search
+        .best
+        .map(|(_, x)| x.into_iter().map(|v| v as i64).collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::min_sum;
+    use crate::utils::rng::XorShift;
+
+    /// The rows of the system where column `j` has a 1 at each of `columns[j]`.
+    fn rows(columns: &[&[usize]], len: usize) -> Vec<Vec<i64>> {
+        (0..len)
+            .map(|i| columns.iter().map(|c| c.contains(&i) as i64).collect())
+            .collect()
+    }
+
+    fn check(a: &[Vec<i64>], b: &[i64], x: &[i64]) {
+        for (row, v) in a.iter().zip(b) {
+            assert_eq!(row.iter().zip(x).map(|(c, x)| c * x).sum::<i64>(), *v);
+        }
+    }
+
+    /// Buttons, joltages and fewest presses.
+    type Machine = (&'static [&'static [usize]], &'static [i64], i64);
+
+    #[test]
+    fn test_min_sum() {
+        let machines: [Machine; 3] = [
+            (
+                &[&[3], &[1, 3], &[2], &[2, 3], &[0, 2], &[0, 1]],
+                &[3, 5, 4, 7],
+                10,
+            ),
+            (
+                &[&[0, 2, 3, 4], &[2, 3], &[0, 4], &[0, 1, 2], &[1, 2, 3, 4]],
+                &[7, 5, 12, 7, 2],
+                12,
+            ),
+            (
+                &[&[0, 1, 2, 3, 4], &[0, 3, 4], &[0, 1, 2, 4, 5], &[1, 2]],
+                &[10, 11, 11, 5, 10, 5],
+                11,
+            ),
+        ];
+        for (buttons, jolts, presses) in machines {
+            let a = rows(buttons, jolts.len());
+            let x = min_sum(&a, jolts).unwrap();
+            check(&a, jolts, &x);
+            assert_eq!(x.iter().sum::<i64>(), presses);
+        }
+    }
+
+    #[test]
+    fn test_many_free_variables() {
+        // 13 buttons on 4 counters leave 9 free variables, and 5 when only
+        // the first 9 buttons are kept
+        let buttons: &[&[usize]] = &[
+            &[0],
+            &[1],
+            &[2],
+            &[3],
+            &[0, 1],
+            &[1, 2],
+            &[2, 3],
+            &[0, 3],
+            &[0, 2],
+            &[1, 3],
+            &[0, 1, 2],
+            &[1, 2, 3],
+            &[0, 1, 2, 3],
+        ];
+        let a = rows(buttons, 4);
+        let x = min_sum(&a, &[60, 60, 60, 60]).unwrap();
+        assert_eq!(x.iter().sum::<i64>(), 60);
+
+        let x = min_sum(&a, &[60, 70, 80, 90]).unwrap();
+        check(&a, &[60, 70, 80, 90], &x);
+        assert_eq!(x.iter().sum::<i64>(), 90);
+
+        let a = rows(&buttons[..9], 4);
+        let x = min_sum(&a, &[60, 70, 80, 90]).unwrap();
+        check(&a, &[60, 70, 80, 90], &x);
+    }
+
+    #[test]
+    fn test_infeasible() {
+        // counter 2 is never incremented
+        let a = rows(&[&[0], &[0, 1]], 3);
+        assert_eq!(min_sum(&a, &[1, 1, 1]), None);
+        // both counters move together
+        let a = rows(&[&[0, 1]], 2);
+        assert_eq!(min_sum(&a, &[2, 3]), None);
+        // 2x = 3
+        assert_eq!(min_sum(&[vec![2]], &[3]), None);
+        assert_eq!(min_sum(&[vec![1]], &[-1]), None);
+    }
+
+    #[test]
+    fn test_brute_force() {
+        let mut rng = XorShift::new(0x853c_49e6_748f_ea9b);
+        let mut random = |n: u64| rng.below(n) as i64;
+
+        for _ in 0..100 {
+            let (m, n) = (3, 4);
+            let a: Vec<Vec<i64>> = (0..m)
+                .map(|_| (0..n).map(|_| random(3)).collect())
+                .collect();
+            let b: Vec<i64> = (0..m).map(|_| random(8)).collect();
+
+            let mut best = None;
+            for code in 0..9i64.pow(n as u32) {
+                let x: Vec<i64> = (0..n).map(|j| code / 9i64.pow(j as u32) % 9).collect();
+                let fits = a
+                    .iter()
+                    .zip(&b)
+                    .all(|(row, v)| row.iter().zip(&x).map(|(c, x)| c * x).sum::<i64>() == *v);
+                if fits {
+                    let sum: i64 = x.iter().sum();
+                    best = Some(best.map_or(sum, |best: i64| best.min(sum)));
+                }
+            }
+
+            let x = min_sum(&a, &b);
+            if let Some(x) = &x {
+                check(&a, &b, x);
+            }
+            assert_eq!(x.map(|x| x.iter().sum::<i64>()), best, "{:?} {:?}", a, b);
+        }
+    }
+}
@@ -1,7 +1,206 @@
-use std::fs;
+use std::{fmt, fs};
+
+/// The clean-ups applied to an input before it is handed to a day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Normalize {
+    /// Drop a leading UTF-8 byte order mark.
+    pub bom: bool,
+    /// Turn `\r\n` line endings into `\n`.
+    pub crlf: bool,
+    /// Strip spaces and tabs at the end of each line.
+    pub trailing_spaces: bool,
+    /// Drop blank lines at the end of the input.
+    pub trailing_blank_lines: bool,
+    /// Make sure the last line ends with `\n`.
+    pub final_newline: bool,
+}
+
+impl Normalize {
+    pub const ALL: Normalize = Normalize {
+        bom: true,
+        crlf: true,
+        trailing_spaces: true,
+        trailing_blank_lines: true,
+        final_newline: true,
+    };
+
+    #[allow(dead_code)]
+    pub const NONE: Normalize = Normalize {
+        bom: false,
+        crlf: false,
+        trailing_spaces: false,
+        trailing_blank_lines: false,
+        final_newline: false,
+    };
+}
+
+/// What [`normalize`] changed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Report {
+    pub bom: bool,
+    pub crlf_lines: usize,
+    pub trailing_space_lines: usize,
+    pub trailing_blank_lines: usize,
+    pub final_newline: bool,
+}
+
+impl Report {
+    pub fn is_empty(&self) -> bool {
+        *self == Report::default()
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut changes = Vec::new();
+        if self.bom {
+            changes.push("removed the byte order mark".to_owned());
+        }
+        if self.crlf_lines > 0 {
+            changes.push(format!("converted {} CRLF line endings", self.crlf_lines));
+        }
+        if self.trailing_space_lines > 0 {
+            changes.push(format!(
+                "trimmed trailing spaces on {} lines",
+                self.trailing_space_lines
+            ));
+        }
+        if self.trailing_blank_lines > 0 {
+            changes.push(format!(
+                "removed {} blank lines at the end",
+                self.trailing_blank_lines
+            ));
+        }
+        if self.final_newline {
+            changes.push("added the final newline".to_owned());
+        }
+        write!(f, "{}", changes.join(", "))
+    }
+}
+
+pub fn normalize(input: &str, opts: &Normalize) -> (String, Report) {
+    let mut report = Report::default();
+
+    let mut input = input;
+    if opts.bom {
+        if let Some(stripped) = input.strip_prefix('\u{feff}') {
+            input = stripped;
+            report.bom = true;
+        }
+    }
+
+    let mut lines: Vec<&str> = input.split('\n').collect();
+    // text after the last '\n', empty when the input ends with a newline
+    let last = lines.pop().unwrap();
+    let had_final_newline = last.is_empty();
+    if !had_final_newline {
+        lines.push(last);
+    }
+
+    let mut lines: Vec<String> = lines
+        .into_iter()
+        .map(|line| {
+            let mut line = line;
+            if opts.crlf {
+                if let Some(stripped) = line.strip_suffix('\r') {
+                    line = stripped;
+                    report.crlf_lines += 1;
+                }
+            }
+            if opts.trailing_spaces {
+                let trimmed = line.trim_end_matches([' ', '\t']);
+                if trimmed.len() != line.len() {
+                    line = trimmed;
+                    report.trailing_space_lines += 1;
+                }
+            }
+            line.to_owned()
+        })
+        .collect();
+
+    if opts.trailing_blank_lines {
+        while lines.last().is_some_and(|l| l.trim().is_empty()) {
+            lines.pop();
+            report.trailing_blank_lines += 1;
+        }
+    }
+
+    let mut output = lines.join("\n");
+    if !lines.is_empty() && (had_final_newline || opts.final_newline) {
+        output.push('\n');
+        report.final_newline = !had_final_newline;
+    }
+
+    (output, report)
+}
 
 pub fn read_input(modpath: &str) -> String {
+    read_input_with(modpath, &Normalize::ALL)
+}
+
+/// Reads the input of the day in `modpath`, applying only the chosen clean-ups.
+pub fn read_input_with(modpath: &str, opts: &Normalize) -> String {
     let i = modpath.len() - 2;
     let fname = format!("inputs/input_{}.txt", &modpath[i..]);
-    fs::read_to_string(fname).unwrap().replace("\r\n", "\n")
+    let (input, report) = normalize(&fs::read_to_string(&fname).unwrap(), opts);
+    if !report.is_empty() {
+        eprintln!("\x1b[33mnote:\x1b[m {}: {}", fname, report);
+    }
+    input
+}
+
+#[cfg(test)]
+mod test {
+    use super::{normalize, Normalize, Report};
+
+    #[test]
+    fn test_normalize() {
+        let (output, report) = normalize(
+            "\u{feff}3-5 \r\n10-14\r\n\r\n1\t\n5\n\n  \n",
+            &Normalize::ALL,
+        );
+        assert_eq!(output, "3-5\n10-14\n\n1\n5\n");
+        assert_eq!(
+            report,
+            Report {
+                bom: true,
+                crlf_lines: 3,
+                trailing_space_lines: 3,
+                trailing_blank_lines: 2,
+                final_newline: false,
+            }
+        );
+        assert_eq!(
+            report.to_string(),
+            "removed the byte order mark, converted 3 CRLF line endings, \
+            trimmed trailing spaces on 3 lines, removed 2 blank lines at the end"
+        );
+    }
+
+    #[test]
+    fn test_normalize_final_newline() {
+        let (output, report) = normalize("L68\nL30", &Normalize::ALL);
+        assert_eq!(output, "L68\nL30\n");
+        assert!(report.final_newline);
+
+        let (output, report) = normalize("L68\nL30\n", &Normalize::ALL);
+        assert_eq!(output, "L68\nL30\n");
+        assert!(report.is_empty());
+    }
+
+    #[test]
+    fn test_normalize_opt_out() {
+        let opts = Normalize {
+            trailing_spaces: false,
+            ..Normalize::ALL
+        };
+        let (output, report) = normalize("123 328 \r\n*   +   \r\n\r\n", &opts);
+        assert_eq!(output, "123 328 \n*   +   \n");
+        assert_eq!(report.trailing_space_lines, 0);
+
+        let input = "\u{feff}a \r\n\n";
+        let (output, report) = normalize(input, &Normalize::NONE);
+        assert_eq!(output, input);
+        assert!(report.is_empty());
+    }
 }
@@ -0,0 +1,235 @@
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+/// A point as its integer coordinates.
+pub type Coords<const D: usize> = [i64; D];
+
+/// A static k-d tree over integer points, for nearest neighbour queries by
+/// squared euclidean distance.
+///
+/// Points keep the index they had in the slice the tree was built from, and
+/// equally distant points come out by increasing index.
+#[derive(Debug, Clone)]
+pub struct KdTree<const D: usize> {
+    points: Vec<Coords<D>>,
+    /// The point indices, laid out as an implicit tree: the node of a slice
+    /// is its middle element, split along `depth % D`.
+    order: Vec<usize>,
+}
+
+fn dist_sq<const D: usize>(a: &Coords<D>, b: &Coords<D>) -> i64 {
+    a.iter().zip(b).map(|(a, b)| (a - b) * (a - b)).sum()
+}
+
+impl<const D: usize> KdTree<D> {
+    pub fn new(points: &[Coords<D>]) -> Self {
+        let mut tree = KdTree {
+            points: points.to_vec(),
+            order: (0..points.len()).collect(),
+        };
+        tree.build(0, points.len(), 0);
+        tree
+    }
+
+    fn build(&mut self, lo: usize, hi: usize, depth: usize) {
+        if hi - lo <= 1 {
+            return;
+        }
+        let mid = (lo + hi) / 2;
+        let axis = depth % D;
+        let points = &self.points;
+        self.order[lo..hi].select_nth_unstable_by_key(mid - lo, |&i| points[i][axis]);
+        self.build(lo, mid, depth + 1);
+        self.build(mid + 1, hi, depth + 1);
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// The `k` points closest to `target`, closest first, as
+    /// `(squared distance, index)`.
+    #[allow(dead_code)]
+    pub fn nearest(&self, target: &Coords<D>, k: usize) -> Vec<(i64, usize)> {
+        self.nearest_where(target, k, |_| true)
+    }
+
+    /// Same as [`KdTree::nearest`], among the points whose index passes `keep`.
+    pub fn nearest_where(
+        &self,
+        target: &Coords<D>,
+        k: usize,
+        keep: impl Fn(usize) -> bool,
+    ) -> Vec<(i64, usize)> {
+        let mut best = BinaryHeap::with_capacity(k + 1);
+        if k > 0 {
+            self.search(target, k, &keep, 0, self.len(), 0, &mut best);
+        }
+        best.into_sorted_vec()
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn search(
+        &self,
+        target: &Coords<D>,
+        k: usize,
+        keep: &impl Fn(usize) -> bool,
+        lo: usize,
+        hi: usize,
+        depth: usize,
+        best: &mut BinaryHeap<(i64, usize)>,
+    ) {
+        if lo >= hi {
+            return;
+        }
+        let mid = (lo + hi) / 2;
+        let index = self.order[mid];
+        let point = &self.points[index];
+
+        if keep(index) {
+            best.push((dist_sq(target, point), index));
+            if best.len() > k {
+                best.pop();
+            }
+        }
+
+        let axis = depth % D;
+        let diff = target[axis] - point[axis];
+        let (near, far) = if diff < 0 {
+            ((lo, mid), (mid + 1, hi))
+        } else {
+            ((mid + 1, hi), (lo, mid))
+        };
+        self.search(target, k, keep, near.0, near.1, depth + 1, best);
+        // a tie with the worst kept point may still win on its index
+        if best.len() < k || diff * diff <= best.peek().unwrap().0 {
+            self.search(target, k, keep, far.0, far.1, depth + 1, best);
+        }
+    }
+
+    /// Every pair of distinct points, closest first, computed as they are
+    /// consumed.
+    pub fn nearest_pairs(&self) -> NearestPairs<'_, D> {
+        let mut pairs = NearestPairs {
+            tree: self,
+            neighbours: vec![Vec::new(); self.len()],
+            asked: vec![0; self.len()],
+            next: vec![0; self.len()],
+            queue: BinaryHeap::new(),
+        };
+        for i in 0..self.len() {
+            pairs.queue_next(i);
+        }
+        pairs
+    }
+}
+
+/// The pairs of points of a [`KdTree`] as `(i, j, squared distance)` with
+/// `i < j`, by increasing distance, then `i`, then `j`.
+///
+/// Each point only looks up its neighbours of higher index, a few at a time
+/// and more whenever it runs out, so taking the first few pairs costs far
+/// less than sorting all of them.
+#[derive(Debug, Clone)]
+pub struct NearestPairs<'a, const D: usize> {
+    tree: &'a KdTree<D>,
+    /// The closest higher-index neighbours found so far for each point.
+    neighbours: Vec<Vec<(i64, usize)>>,
+    /// How many neighbours each point last looked up.
+    asked: Vec<usize>,
+    /// How many of them each point has already paired with.
+    next: Vec<usize>,
+    /// The next pair of each point that still has one.
+    queue: BinaryHeap<Reverse<(i64, usize, usize)>>,
+}
+
+const FIRST_BATCH: usize = 4;
+
+impl<const D: usize> NearestPairs<'_, D> {
+    fn queue_next(&mut self, i: usize) {
+        let (found, next) = (self.neighbours[i].len(), self.next[i]);
+        if next == found {
+            // a short batch means there is nobody left
+            if found < self.asked[i] {
+                return;
+            }
+            let k = (found * 2).max(FIRST_BATCH);
+            self.asked[i] = k;
+            self.neighbours[i] = self.tree.nearest_where(&self.tree.points[i], k, |j| j > i);
+            if self.neighbours[i].len() == next {
+                return;
+            }
+        }
+        let (dist, j) = self.neighbours[i][next];
+        self.next[i] += 1;
+        self.queue.push(Reverse((dist, i, j)));
+    }
+}
+
+impl<const D: usize> Iterator for NearestPairs<'_, D> {
+    type Item = (usize, usize, i64);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let Reverse((dist, i, j)) = self.queue.pop()?;
+        self.queue_next(i);
+        Some((i, j, dist))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{dist_sq, Coords, KdTree};
+    use crate::utils::rng::XorShift;
+
+    /// Deterministic pseudo-random points, with plenty of ties.
+    fn points<const D: usize>(n: usize, range: i64) -> Vec<Coords<D>> {
+        let mut rng = XorShift::new(0x2545_f491_4f6c_dd1d);
+        (0..n)
+            .map(|_| [0; D].map(|_| rng.below(range as u64) as i64 - range / 2))
+            .collect()
+    }
+
+    fn all_pairs<const D: usize>(points: &[Coords<D>]) -> Vec<(usize, usize, i64)> {
+        let mut pairs = Vec::new();
+        for i in 0..points.len() {
+            for j in i + 1..points.len() {
+                pairs.push((i, j, dist_sq(&points[i], &points[j])));
+            }
+        }
+        pairs.sort_by_key(|&(i, j, d)| (d, i, j));
+        pairs
+    }
+
+    #[test]
+    fn test_nearest() {
+        let points = points::<2>(200, 50);
+        let tree = KdTree::new(&points);
+        let target = [3, -7];
+
+        let mut expected: Vec<(i64, usize)> = points
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (dist_sq(&target, p), i))
+            .collect();
+        expected.sort();
+        expected.truncate(15);
+        assert_eq!(tree.nearest(&target, 15), expected);
+        assert_eq!(tree.nearest(&target, 0), vec![]);
+        assert_eq!(tree.nearest(&target, 1000).len(), 200);
+    }
+
+    #[test]
+    fn test_nearest_pairs() {
+        let points = points::<3>(120, 40);
+        let tree = KdTree::new(&points);
+        let pairs: Vec<_> = tree.nearest_pairs().collect();
+        assert_eq!(pairs, all_pairs(&points));
+
+        assert_eq!(KdTree::<3>::new(&[]).nearest_pairs().next(), None);
+        assert_eq!(KdTree::new(&[[1, 2, 3]]).nearest_pairs().next(), None);
+    }
+}
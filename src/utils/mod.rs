@@ -1 +1,16 @@
+pub mod compress;
+pub mod dsu;
+pub mod geom;
+pub mod gf2;
+pub mod grid;
+pub mod ilp;
 pub mod input;
+pub mod kdtree;
+pub mod options;
+pub mod parse;
+pub mod polygon;
+pub mod ranges;
+pub mod render;
+#[cfg(test)]
+pub mod rng;
+pub mod search;
@@ -0,0 +1,164 @@
+//! Settings given on the command line, for days that can show more than
+//! their answer.
+//!
+//! The runner takes them out of its arguments once with [`init`], then days
+//! read them back with [`get`].
+
+use std::{collections::HashMap, path::PathBuf, sync::OnceLock, time::Duration};
+
+use super::render::Rgb;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Options {
+    /// Whether day 1 lists each rotation and its passes through 0.
+    pub trace: bool,
+    /// How many of day 1's first rotations to draw on a dial.
+    pub dial: Option<usize>,
+    /// Where to write day 4's removal rounds as an animated GIF.
+    pub render: Option<PathBuf>,
+    /// Whether day 4 plays its removal rounds in the terminal.
+    pub animate: bool,
+    /// Whether day 4 shows the round each roll was removed in.
+    pub heatmap: bool,
+    /// Where to draw day 4's heatmap as a PNG image.
+    pub heatmap_png: Option<PathBuf>,
+    /// The time between frames of animations.
+    pub delay: Option<Duration>,
+    /// Whether day 6 shows the columns it finds in the worksheet.
+    pub columns: bool,
+    /// Whether day 7 shows its beams and the splitters they hit.
+    pub beams: bool,
+    /// Same as `beams`, with the timelines through each column after each row.
+    pub beam_counts: bool,
+    /// Where to write day 8's circuits as a Graphviz graph.
+    pub dot: Option<PathBuf>,
+    /// Where to write day 8's circuits as a PLY point cloud.
+    pub ply: Option<PathBuf>,
+    /// Whether day 8 exports the connections spanning all boxes from part 2,
+    /// rather than the ones made in part 1.
+    pub spanning: bool,
+    /// Where to draw day 9's tiles and largest rectangles as an SVG picture.
+    pub svg: Option<PathBuf>,
+    /// A machine, written as a line of input, for day 10 to detail before
+    /// solving.
+    pub inspect: Option<String>,
+    /// Colours by name, overriding the defaults of the renders.
+    pub colors: HashMap<String, Rgb>,
+}
+
+/// The options every runner accepts, for its usage line.
+pub const USAGE: &str = "[--trace] [--dial <steps>] [--render <out.gif>] [--animate] [--delay <ms>] [--heatmap] [--heatmap-png <out.png>] [--columns] [--svg <out.svg>] [--beams | --beam-counts] [--dot <out.dot>] [--ply <out.ply>] [--spanning] [--inspect <machine>] [--colors <name>=<#rrggbb>,...]";
+
+impl Options {
+    /// Takes the options out of `args`, and returns them with the remaining
+    /// arguments.
+    pub fn parse<'a>(args: &[&'a str]) -> Result<(Options, Vec<&'a str>), String> {
+        let mut options = Options::default();
+        let mut rest = Vec::new();
+        let mut args = args.iter();
+
+        while let Some(&arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .copied()
+                    .ok_or_else(|| format!("{} needs a value", arg))
+            };
+            match arg {
+                "--trace" => options.trace = true,
+                "--dial" => {
+                    let steps = value()?;
+                    let steps = steps
+                        .parse()
+                        .map_err(|_| format!("expected a number of steps, found {:?}", steps))?;
+                    options.dial = Some(steps);
+                }
+                "--render" => options.render = Some(value()?.into()),
+                "--columns" => options.columns = true,
+                "--beams" => options.beams = true,
+                "--beam-counts" => options.beam_counts = true,
+                "--dot" => options.dot = Some(value()?.into()),
+                "--ply" => options.ply = Some(value()?.into()),
+                "--spanning" => options.spanning = true,
+                "--animate" => options.animate = true,
+                "--heatmap" => options.heatmap = true,
+                "--heatmap-png" => options.heatmap_png = Some(value()?.into()),
+                "--delay" => {
+                    let ms = value()?;
+                    let ms = ms
+                        .parse()
+                        .map_err(|_| format!("expected a delay in milliseconds, found {:?}", ms))?;
+                    options.delay = Some(Duration::from_millis(ms));
+                }
+                "--svg" => options.svg = Some(value()?.into()),
+                "--inspect" => options.inspect = Some(value()?.to_owned()),
+                "--colors" => {
+                    for color in value()?.split(',') {
+                        let (name, rgb) = color.split_once('=').ok_or_else(|| {
+                            format!("expected <name>=<#rrggbb>, found {:?}", color)
+                        })?;
+                        options.colors.insert(name.to_owned(), rgb.parse()?);
+                    }
+                }
+                _ if arg.starts_with("--") => return Err(format!("unknown option {}", arg)),
+                _ => rest.push(arg),
+            }
+        }
+
+        Ok((options, rest))
+    }
+
+    /// The colour named `name`, or `default` if none was given.
+    pub fn color(&self, name: &str, default: Rgb) -> Rgb {
+        self.colors.get(name).copied().unwrap_or(default)
+    }
+}
+
+static OPTIONS: OnceLock<Options> = OnceLock::new();
+
+/// Takes the options out of the command line `args` and makes them the ones
+/// [`get`] returns, then gives back the remaining arguments. Only the first
+/// call counts.
+pub fn init<'a>(args: &[&'a str]) -> Result<Vec<&'a str>, String> {
+    let (options, rest) = Options::parse(args)?;
+    let _ = OPTIONS.set(options);
+    Ok(rest)
+}
+
+/// The options of this run, or the defaults if the runner gave none.
+pub fn get() -> &'static Options {
+    OPTIONS.get_or_init(Options::default)
+}
+
+#[cfg(test)]
+mod test {
+    use super::Options;
+    use crate::utils::render::Rgb;
+
+    #[test]
+    fn test_parse() {
+        let args = [
+            "--render",
+            "out.gif",
+            "submit",
+            "--colors",
+            "roll=#c8a050,empty=000000",
+            "4",
+            "2",
+        ];
+        let (options, rest) = Options::parse(&args).unwrap();
+        assert_eq!(rest, vec!["submit", "4", "2"]);
+        assert_eq!(options.render, Some("out.gif".into()));
+        assert_eq!(options.color("roll", Rgb(0, 0, 0)), Rgb(200, 160, 80));
+        assert_eq!(options.color("empty", Rgb(1, 1, 1)), Rgb(0, 0, 0));
+        assert_eq!(options.color("removed", Rgb(1, 1, 1)), Rgb(1, 1, 1));
+
+        assert!(Options::parse(&["--render"]).is_err());
+        assert!(Options::parse(&["--colors", "roll"]).is_err());
+        assert!(Options::parse(&["--colors", "roll=#c8a05"]).is_err());
+        assert!(Options::parse(&["--frobnicate"]).is_err());
+        assert!(Options::parse(&["--delay", "soon"]).is_err());
+        let (options, _) = Options::parse(&["--animate", "--delay", "40"]).unwrap();
+        assert!(options.animate);
+        assert_eq!(options.delay, Some(std::time::Duration::from_millis(40)));
+    }
+}
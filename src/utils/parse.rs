@@ -0,0 +1,291 @@
+use std::{fmt, ops::Neg, str::FromStr};
+
+/// A piece of input that couldn't be parsed.
+///
+/// Errors are created from the offending slice of the input, and only know
+/// where they come from once [`ParseError::locate`] is given the whole input
+/// that slice was borrowed from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// 1-based, 0 until located.
+    pub line: usize,
+    /// 1-based, in characters, 0 until located.
+    pub column: usize,
+    pub found: String,
+    pub expected: String,
+    /// The full line containing `found`, once located.
+    pub source_line: String,
+    addr: usize,
+}
+
+impl ParseError {
+    pub fn new(found: &str, expected: impl Into<String>) -> Self {
+        ParseError {
+            line: 0,
+            column: 0,
+            found: found.to_owned(),
+            expected: expected.into(),
+            source_line: String::new(),
+            addr: found.as_ptr() as usize,
+        }
+    }
+
+    /// Finds the offending text in `input`, the string it was sliced from.
+    /// Errors that are already located, or that come from another string, are
+    /// returned as they are.
+    pub fn locate(mut self, input: &str) -> Self {
+        let start = input.as_ptr() as usize;
+        if self.line != 0 || self.addr < start || self.addr > start + input.len() {
+            return self;
+        }
+
+        let offset = self.addr - start;
+        let line_start = input[..offset].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = input[offset..]
+            .find('\n')
+            .map_or(input.len(), |i| offset + i);
+        self.line = input[..offset].matches('\n').count() + 1;
+        self.column = input[line_start..offset].chars().count() + 1;
+        self.source_line = input[line_start..line_end].to_owned();
+        self
+    }
+
+    /// The offending line with the bad part underlined, compiler-style.
+    pub fn snippet(&self) -> String {
+        if self.line == 0 {
+            return String::new();
+        }
+        let number = self.line.to_string();
+        let gutter = " ".repeat(number.len());
+        let found = self.found.lines().next().unwrap_or("");
+        let carets = "^".repeat(found.chars().count().max(1));
+
+        format!(
+            "{} |\n{} | {}\n{} | {}{}\n",
+            gutter,
+            number,
+            self.source_line,
+            gutter,
+            " ".repeat(self.column - 1),
+            carets
+        )
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "expected {} but found {:?}", self.expected, self.found)?;
+        if self.line != 0 {
+            write!(f, " at line {}, column {}", self.line, self.column)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Parses `s`, ignoring surrounding whitespace.
+pub fn parse<T: FromStr>(s: &str, expected: &str) -> Result<T, ParseError> {
+    let s = s.trim();
+    s.parse().map_err(|_| ParseError::new(s, expected))
+}
+
+/// Every integer in `s`, whatever text surrounds them.
+///
+/// A `-` counts as a sign only when it doesn't follow a digit, so that
+/// `"11-22"` gives `[11, 22]` while `"x=-3"` gives `[-3]`.
+pub fn ints<T: FromStr>(s: &str) -> Result<Vec<T>, ParseError> {
+    let bytes = s.as_bytes();
+    let mut numbers = Vec::new();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let negative = bytes[i] == b'-'
+            && bytes.get(i + 1).is_some_and(|b| b.is_ascii_digit())
+            && !(i > 0 && bytes[i - 1].is_ascii_digit());
+        if !negative && !bytes[i].is_ascii_digit() {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        i += 1;
+        while i < bytes.len() && bytes[i].is_ascii_digit() {
+            i += 1;
+        }
+        numbers.push(parse(&s[start..i], "an integer")?);
+    }
+
+    Ok(numbers)
+}
+
+/// An integer whose sign is given by a leading character, like `R12` or `L5`.
+pub fn signed_by_prefix<T>(s: &str, positive: char, negative: char) -> Result<T, ParseError>
+where
+    T: FromStr + Neg<Output = T>,
+{
+    if let Some(n) = s.strip_prefix(positive) {
+        parse(n, "an integer")
+    } else if let Some(n) = s.strip_prefix(negative) {
+        Ok(-parse::<T>(n, "an integer")?)
+    } else {
+        let first = s.chars().next().map_or(0, |c| c.len_utf8());
+        Err(ParseError::new(
+            &s[..first],
+            format!("{:?} or {:?}", positive, negative),
+        ))
+    }
+}
+
+/// The items of a `sep`-separated list.
+pub fn list<T: FromStr>(s: &str, sep: char) -> Result<Vec<T>, ParseError> {
+    s.trim()
+        .split(sep)
+        .map(|item| parse(item, "a list item"))
+        .collect()
+}
+
+/// The items of a whitespace-separated list.
+pub fn words<T: FromStr>(s: &str) -> Result<Vec<T>, ParseError> {
+    s.split_whitespace()
+        .map(|word| parse(word, "a word"))
+        .collect()
+}
+
+/// Two values separated by `sep`, like `3-5` or `7,1`.
+pub fn pair<A: FromStr, B: FromStr>(s: &str, sep: char) -> Result<(A, B), ParseError> {
+    let (a, b) = s
+        .split_once(sep)
+        .ok_or_else(|| ParseError::new(s, format!("two values separated by {:?}", sep)))?;
+    Ok((parse(a, "a value")?, parse(b, "a value")?))
+}
+
+/// Three values separated by `sep`, like `162,817,812`.
+pub fn triple<A: FromStr, B: FromStr, C: FromStr>(
+    s: &str,
+    sep: char,
+) -> Result<(A, B, C), ParseError> {
+    let mut parts = s.splitn(3, sep);
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(a), Some(b), Some(c)) => Ok((
+            parse(a, "a value")?,
+            parse(b, "a value")?,
+            parse(c, "a value")?,
+        )),
+        _ => Err(ParseError::new(
+            s,
+            format!("three values separated by {:?}", sep),
+        )),
+    }
+}
+
+/// The blocks of lines separated by blank lines.
+pub fn sections(s: &str) -> Vec<&str> {
+    s.split("\n\n")
+        .filter(|sec| !sec.trim().is_empty())
+        .collect()
+}
+
+/// One value per line.
+pub fn records<T: FromStr>(s: &str) -> Result<Vec<T>, ParseError> {
+    s.lines().map(|line| parse(line, "a record")).collect()
+}
+
+/// The characters of each line, which must all be among `allowed`.
+pub fn char_grid(s: &str, allowed: &str) -> Result<Vec<Vec<char>>, ParseError> {
+    s.lines()
+        .map(|line| {
+            line.char_indices()
+                .map(|(i, c)| {
+                    if allowed.contains(c) {
+                        Ok(c)
+                    } else {
+                        let found = &line[i..i + c.len_utf8()];
+                        Err(ParseError::new(found, format!("one of {:?}", allowed)))
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_ints() {
+        assert_eq!(ints::<i64>("x=-3, y=12..-7"), Ok(vec![-3, 12, -7]));
+        assert_eq!(ints::<u64>("11-22,95-115"), Ok(vec![11, 22, 95, 115]));
+        assert_eq!(ints::<u16>("(0,2,3,4) {7,5}"), Ok(vec![0, 2, 3, 4, 7, 5]));
+        assert_eq!(ints::<i32>("no numbers - here"), Ok(vec![]));
+        assert!(ints::<u8>("1,256").is_err());
+    }
+
+    #[test]
+    fn test_signed_by_prefix() {
+        assert_eq!(signed_by_prefix::<i32>("R48", 'R', 'L'), Ok(48));
+        assert_eq!(signed_by_prefix::<i32>("L5", 'R', 'L'), Ok(-5));
+        let err = signed_by_prefix::<i32>("U3", 'R', 'L').unwrap_err();
+        assert_eq!(err.found, "U");
+        assert_eq!(err.expected, "'R' or 'L'");
+    }
+
+    #[test]
+    fn test_lists() {
+        assert_eq!(list::<u8>("1, 2,3\n", ','), Ok(vec![1, 2, 3]));
+        assert_eq!(words::<i64>(" 123 328  51 64 "), Ok(vec![123, 328, 51, 64]));
+        assert_eq!(pair::<u64, u64>("3-5", '-'), Ok((3, 5)));
+        assert_eq!(
+            triple::<i64, i64, i64>("162,817,812", ','),
+            Ok((162, 817, 812))
+        );
+        assert_eq!(
+            triple::<i64, i64, i64>("162,817", ',').unwrap_err().found,
+            "162,817"
+        );
+        assert_eq!(
+            triple::<i64, i64, i64>("162,8x7,812", ',')
+                .unwrap_err()
+                .found,
+            "8x7"
+        );
+    }
+
+    #[test]
+    fn test_sections() {
+        let input = "3-5\n10-14\n\n1\n5\n";
+        let secs = sections(input);
+        assert_eq!(secs, vec!["3-5\n10-14", "1\n5\n"]);
+        assert_eq!(records::<u64>(secs[1]), Ok(vec![1, 5]));
+        assert_eq!(
+            char_grid(".@\n@.", ".@"),
+            Ok(vec![vec!['.', '@'], vec!['@', '.']])
+        );
+    }
+
+    #[test]
+    fn test_locate() {
+        let input = "L68\nL30\nRx8\n";
+        let line = input.lines().nth(2).unwrap();
+        let err = signed_by_prefix::<i32>(line, 'R', 'L')
+            .unwrap_err()
+            .locate(input);
+
+        assert_eq!((err.line, err.column), (3, 2));
+        assert_eq!(err.found, "x8");
+        assert_eq!(err.source_line, "Rx8");
+        assert_eq!(
+            err.to_string(),
+            "expected an integer but found \"x8\" at line 3, column 2"
+        );
+        assert_eq!(err.snippet(), "  |\n3 | Rx8\n  |  ^^\n");
+
+        // slices of another string can't be located
+        let other = String::from("Rx8");
+        let err = signed_by_prefix::<i32>(&other, 'R', 'L')
+            .unwrap_err()
+            .locate(input);
+        assert_eq!(err.line, 0);
+        assert_eq!(err.snippet(), "");
+    }
+}
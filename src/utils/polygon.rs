@@ -0,0 +1,317 @@
+use super::geom::{BoundingBox, Point2};
+
+type Point = Point2<i64>;
+
+/// An edge, as the constant coordinate and the range of the other one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Edge {
+    pos: i64,
+    start: i64,
+    end: i64,
+}
+
+/// A loop of tiles whose edges are all horizontal or vertical, with its
+/// vertices at tile centres.
+///
+/// A tile is inside if its centre is on the loop or enclosed by it, and a
+/// rectangle is inside if all its tiles are. Two edges one tile apart leave
+/// no tile between them, so a rectangle can span both even though the plane
+/// between them is outside the loop.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RectilinearPolygon {
+    vertices: Vec<Point>,
+    vertical: Vec<Edge>,
+    horizontal: Vec<Edge>,
+}
+
+impl RectilinearPolygon {
+    /// The polygon going through `vertices` in order and back to the first.
+    /// Fails with the index of the first vertex that isn't aligned with the
+    /// next one.
+    pub fn new(vertices: Vec<Point>) -> Result<Self, usize> {
+        let mut vertical = Vec::new();
+        let mut horizontal = Vec::new();
+        for (i, p1) in vertices.iter().enumerate() {
+            let p2 = &vertices[(i + 1) % vertices.len()];
+            if p1.x == p2.x {
+                vertical.push(Edge {
+                    pos: p1.x,
+                    start: p1.y.min(p2.y),
+                    end: p1.y.max(p2.y),
+                });
+            } else if p1.y == p2.y {
+                horizontal.push(Edge {
+                    pos: p1.y,
+                    start: p1.x.min(p2.x),
+                    end: p1.x.max(p2.x),
+                });
+            } else {
+                return Err(i);
+            }
+        }
+
+        Ok(RectilinearPolygon {
+            vertices,
+            vertical,
+            horizontal,
+        })
+    }
+
+    pub fn vertices(&self) -> &[Point] {
+        &self.vertices
+    }
+
+    /// Whether the tile at `p` is inside.
+    pub fn contains_point(&self, p: &Point) -> bool {
+        let on_edge = |edges: &[Edge], pos: i64, along: i64| {
+            edges
+                .iter()
+                .any(|e| e.pos == pos && e.start <= along && along <= e.end)
+        };
+        if on_edge(&self.vertical, p.x, p.y) || on_edge(&self.horizontal, p.y, p.x) {
+            return true;
+        }
+
+        // cast a ray towards +x, counting each vertex on its way only once
+        let crossings = self
+            .vertical
+            .iter()
+            .filter(|e| e.pos > p.x && e.start <= p.y && p.y < e.end)
+            .count();
+        crossings % 2 == 1
+    }
+
+    /// Whether every tile of `r`, borders included, is inside.
+    pub fn contains_rect(&self, r: &BoundingBox<Point>) -> bool {
+        // No edge runs strictly between two consecutive vertex coordinates,
+        // so one tile of each strip between them stands for the whole strip.
+        let samples = |min: i64, max: i64, coord: fn(&Point) -> i64| {
+            let mut stops: Vec<i64> = self
+                .vertices
+                .iter()
+                .map(coord)
+                .filter(|&c| min < c && c < max)
+                .chain([min, max])
+                .collect();
+            stops.sort_unstable();
+            stops.dedup();
+            let strips: Vec<i64> = stops
+                .windows(2)
+                .filter(|w| w[1] - w[0] > 1)
+                .map(|w| w[0] + 1)
+                .collect();
+            stops.extend(strips);
+            stops
+        };
+        let xs = samples(r.min.x, r.max.x, |v| v.x);
+        let ys = samples(r.min.y, r.max.y, |v| v.y);
+        xs.iter()
+            .all(|&x| ys.iter().all(|&y| self.contains_point(&Point2::new(x, y))))
+    }
+
+    /// The area enclosed by the loop through the tile centres.
+    pub fn area(&self) -> i64 {
+        let n = self.vertices.len();
+        let twice: i64 = (0..n)
+            .map(|i| {
+                let (p, q) = (self.vertices[i], self.vertices[(i + 1) % n]);
+                p.x * q.y - q.x * p.y
+            })
+            .sum();
+        twice.abs() / 2
+    }
+
+    /// The length of the loop through the tile centres.
+    pub fn perimeter(&self) -> i64 {
+        self.vertical
+            .iter()
+            .chain(&self.horizontal)
+            .map(|e| e.end - e.start)
+            .sum()
+    }
+
+    /// The number of tiles inside.
+    pub fn lattice_points(&self) -> i64 {
+        // Pick's theorem
+        self.area() + self.perimeter() / 2 + 1
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use super::{Point, RectilinearPolygon};
+    use crate::utils::geom::{BoundingBox, Point2};
+
+    fn polygon(vertices: &[(i64, i64)]) -> RectilinearPolygon {
+        RectilinearPolygon::new(vertices.iter().map(|&(x, y)| Point2::new(x, y)).collect()).unwrap()
+    }
+
+    /// The points of the plane inside the loop, at doubled resolution so that
+    /// the outside can flood between edges one tile apart: the loop is drawn,
+    /// and everything the outside can't flood into is inside. Tiles are the
+    /// points at even coordinates.
+    fn rasterize(poly: &RectilinearPolygon) -> HashSet<(i64, i64)> {
+        let bb = BoundingBox::<Point>::from_points(poly.vertices()).unwrap();
+        let (lo, hi) = (
+            bb.min * 2 - Point2::new(2, 2),
+            bb.max * 2 + Point2::new(2, 2),
+        );
+
+        let n = poly.vertices().len();
+        let mut boundary = HashSet::new();
+        for i in 0..n {
+            let (p, q) = (poly.vertices()[i] * 2, poly.vertices()[(i + 1) % n] * 2);
+            for x in p.x.min(q.x)..=p.x.max(q.x) {
+                for y in p.y.min(q.y)..=p.y.max(q.y) {
+                    boundary.insert((x, y));
+                }
+            }
+        }
+
+        let mut outside = HashSet::from([(lo.x, lo.y)]);
+        let mut todo = vec![(lo.x, lo.y)];
+        while let Some((x, y)) = todo.pop() {
+            for next in [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)] {
+                let in_box = lo.x <= next.0 && next.0 <= hi.x && lo.y <= next.1 && next.1 <= hi.y;
+                if in_box && !boundary.contains(&next) && outside.insert(next) {
+                    todo.push(next);
+                }
+            }
+        }
+
+        let mut inside = HashSet::new();
+        for x in lo.x..=hi.x {
+            for y in lo.y..=hi.y {
+                if !outside.contains(&(x, y)) {
+                    inside.insert((x, y));
+                }
+            }
+        }
+        inside
+    }
+
+    /// Checks every query against the tiles of the rasterized polygon, on
+    /// every tile and rectangle around it.
+    fn check(poly: &RectilinearPolygon) {
+        let inside = rasterize(poly);
+        let bb = BoundingBox::<Point>::from_points(poly.vertices()).unwrap();
+        let xs = bb.min.x - 1..=bb.max.x + 1;
+        let ys = bb.min.y - 1..=bb.max.y + 1;
+
+        let points: Vec<Point> = xs
+            .clone()
+            .flat_map(|x| ys.clone().map(move |y| Point2::new(x, y)))
+            .collect();
+        for p in points.iter() {
+            assert_eq!(
+                poly.contains_point(p),
+                inside.contains(&(2 * p.x, 2 * p.y)),
+                "{}",
+                p
+            );
+        }
+        for p1 in points.iter() {
+            for p2 in points.iter() {
+                let r = BoundingBox::<Point>::from_corners(p1, p2);
+                let expected = (r.min.x..=r.max.x)
+                    .all(|x| (r.min.y..=r.max.y).all(|y| inside.contains(&(2 * x, 2 * y))));
+                assert_eq!(poly.contains_rect(&r), expected, "{} {}", r.min, r.max);
+            }
+        }
+
+        // unit squares have their centre at odd doubled coordinates
+        let squares = inside
+            .iter()
+            .filter(|(x, y)| x % 2 != 0 && y % 2 != 0)
+            .count();
+        let lattice = inside
+            .iter()
+            .filter(|(x, y)| x % 2 == 0 && y % 2 == 0)
+            .count();
+        assert_eq!(poly.area(), squares as i64);
+        assert_eq!(poly.lattice_points(), lattice as i64);
+    }
+
+    #[test]
+    fn test_red_tiles() {
+        let poly = polygon(&[
+            (7, 1),
+            (11, 1),
+            (11, 7),
+            (9, 7),
+            (9, 5),
+            (2, 5),
+            (2, 3),
+            (7, 3),
+        ]);
+        assert_eq!(poly.area(), 30);
+        assert_eq!(poly.perimeter(), 30);
+        assert_eq!(poly.lattice_points(), 46);
+        assert!(poly.contains_rect(&BoundingBox::<Point>::from_corners(
+            &Point2::new(9, 5),
+            &Point2::new(2, 3)
+        )));
+        assert!(!poly.contains_rect(&BoundingBox::<Point>::from_corners(
+            &Point2::new(2, 5),
+            &Point2::new(11, 1)
+        )));
+        check(&poly);
+    }
+
+    #[test]
+    fn test_notches() {
+        // a U, and a comb whose teeth touch
+        check(&polygon(&[
+            (0, 0),
+            (6, 0),
+            (6, 5),
+            (4, 5),
+            (4, 2),
+            (2, 2),
+            (2, 5),
+            (0, 5),
+        ]));
+        check(&polygon(&[
+            (0, 0),
+            (7, 0),
+            (7, 4),
+            (5, 4),
+            (5, 1),
+            (4, 1),
+            (4, 4),
+            (3, 4),
+            (3, 2),
+            (1, 2),
+            (1, 4),
+            (0, 4),
+        ]));
+        // a notch as wide as one tile leaves no tile outside
+        let poly = polygon(&[
+            (13, 10),
+            (15, 10),
+            (15, 11),
+            (16, 11),
+            (16, 12),
+            (15, 12),
+            (15, 13),
+            (13, 13),
+            (13, 12),
+            (14, 12),
+            (14, 11),
+            (13, 11),
+        ]);
+        assert!(poly.contains_rect(&BoundingBox::<Point>::from_corners(
+            &Point2::new(13, 10),
+            &Point2::new(15, 13)
+        )));
+        assert_eq!(poly.lattice_points(), 14);
+        check(&poly);
+
+        assert_eq!(
+            RectilinearPolygon::new(vec![Point2::new(0, 0), Point2::new(1, 1)]),
+            Err(0)
+        );
+    }
+}
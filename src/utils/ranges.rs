@@ -0,0 +1,217 @@
+use std::{
+    iter::Sum,
+    ops::{Range, Sub},
+};
+
+/// A set of values stored as sorted, disjoint, half-open intervals.
+///
+/// Intervals that overlap or touch are merged as soon as they are inserted,
+/// so `3..5` and `5..8` are kept as `3..8`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RangeSet<T> {
+    ranges: Vec<Range<T>>,
+}
+
+impl<T: Copy + Ord> RangeSet<T> {
+    pub fn new() -> Self {
+        RangeSet { ranges: Vec::new() }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// The number of disjoint intervals, not of values.
+    pub fn interval_count(&self) -> usize {
+        self.ranges.len()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Range<T>> {
+        self.ranges.iter()
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        let i = self.ranges.partition_point(|r| r.end <= *value);
+        self.ranges.get(i).is_some_and(|r| r.start <= *value)
+    }
+
+    pub fn insert(&mut self, range: Range<T>) {
+        if range.is_empty() {
+            return;
+        }
+        // the intervals overlapping or touching `range`
+        let i = self.ranges.partition_point(|r| r.end < range.start);
+        let j = self.ranges.partition_point(|r| r.start <= range.end);
+
+        let mut merged = range;
+        if i < j {
+            merged.start = merged.start.min(self.ranges[i].start);
+            merged.end = merged.end.max(self.ranges[j - 1].end);
+        }
+        self.ranges.splice(i..j, [merged]);
+    }
+
+    pub fn remove(&mut self, range: Range<T>) {
+        if range.is_empty() {
+            return;
+        }
+        // the intervals overlapping `range`
+        let i = self.ranges.partition_point(|r| r.end <= range.start);
+        let j = self.ranges.partition_point(|r| r.start < range.end);
+        if i == j {
+            return;
+        }
+
+        let first = self.ranges[i].clone();
+        let last = self.ranges[j - 1].clone();
+        let left = (first.start < range.start).then_some(first.start..range.start);
+        let right = (range.end < last.end).then_some(range.end..last.end);
+        self.ranges.splice(i..j, left.into_iter().chain(right));
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let mut set = self.clone();
+        set.extend(other.iter().cloned());
+        set
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut ranges = Vec::new();
+        let (mut a, mut b) = (
+            self.ranges.iter().peekable(),
+            other.ranges.iter().peekable(),
+        );
+        while let (Some(ra), Some(rb)) = (a.peek(), b.peek()) {
+            let start = ra.start.max(rb.start);
+            let end = ra.end.min(rb.end);
+            if start < end {
+                ranges.push(start..end);
+            }
+            // drop whichever interval ends first, it can't meet anything else
+            if ra.end < rb.end {
+                a.next();
+            } else {
+                b.next();
+            }
+        }
+        RangeSet { ranges }
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut set = self.clone();
+        for range in other.iter() {
+            set.remove(range.clone());
+        }
+        set
+    }
+
+    /// The number of values in the set.
+    pub fn total_len(&self) -> T
+    where
+        T: Sub<Output = T> + Sum,
+    {
+        self.ranges.iter().map(|r| r.end - r.start).sum()
+    }
+}
+
+impl<T: Copy + Ord> Extend<Range<T>> for RangeSet<T> {
+    fn extend<I: IntoIterator<Item = Range<T>>>(&mut self, iter: I) {
+        for range in iter {
+            self.insert(range);
+        }
+    }
+}
+
+impl<T: Copy + Ord> FromIterator<Range<T>> for RangeSet<T> {
+    fn from_iter<I: IntoIterator<Item = Range<T>>>(iter: I) -> Self {
+        let mut set = RangeSet::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<'a, T> IntoIterator for &'a RangeSet<T> {
+    type Item = &'a Range<T>;
+    type IntoIter = std::slice::Iter<'a, Range<T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.ranges.iter()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeSet;
+
+    use super::RangeSet;
+
+    fn intervals(set: &RangeSet<u64>) -> Vec<(u64, u64)> {
+        set.iter().map(|r| (r.start, r.end)).collect()
+    }
+
+    /// The same set, one value at a time.
+    fn values(set: &RangeSet<u64>) -> BTreeSet<u64> {
+        set.iter().flat_map(|r| r.clone()).collect()
+    }
+
+    #[test]
+    fn test_insert() {
+        let mut set: RangeSet<u64> = [3..6, 10..15, 16..21, 12..19].into_iter().collect();
+        assert_eq!(intervals(&set), vec![(3, 6), (10, 21)]);
+        assert_eq!(set.total_len(), 14);
+
+        // touching intervals are merged, empty ones ignored
+        set.insert(6..8);
+        set.insert(9..9);
+        assert_eq!(intervals(&set), vec![(3, 8), (10, 21)]);
+        set.insert(0..40);
+        assert_eq!(intervals(&set), vec![(0, 40)]);
+    }
+
+    #[test]
+    fn test_contains() {
+        let set: RangeSet<u64> = [3..6, 10..15, 16..21, 12..19].into_iter().collect();
+        let fresh: Vec<u64> = [1, 5, 8, 11, 17, 32]
+            .into_iter()
+            .filter(|id| set.contains(id))
+            .collect();
+        assert_eq!(fresh, vec![5, 11, 17]);
+        assert!(set.contains(&3));
+        assert!(!set.contains(&6));
+        assert!(!RangeSet::new().contains(&0));
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut set: RangeSet<u64> = [0..10, 20..30].into_iter().collect();
+        set.remove(5..25);
+        assert_eq!(intervals(&set), vec![(0, 5), (25, 30)]);
+        set.remove(2..3);
+        assert_eq!(intervals(&set), vec![(0, 2), (3, 5), (25, 30)]);
+        set.remove(10..20);
+        assert_eq!(intervals(&set), vec![(0, 2), (3, 5), (25, 30)]);
+        set.remove(0..100);
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let a: RangeSet<u64> = [0..10, 20..30, 40..50].into_iter().collect();
+        let b: RangeSet<u64> = [5..25, 28..42, 60..70].into_iter().collect();
+
+        let union = a.union(&b);
+        let intersection = a.intersection(&b);
+        let difference = a.difference(&b);
+        assert_eq!(intervals(&union), vec![(0, 50), (60, 70)]);
+        assert_eq!(
+            intervals(&intersection),
+            vec![(5, 10), (20, 25), (28, 30), (40, 42)]
+        );
+        assert_eq!(intervals(&difference), vec![(0, 5), (25, 28), (42, 50)]);
+
+        let (va, vb) = (values(&a), values(&b));
+        assert_eq!(values(&union), &va | &vb);
+        assert_eq!(values(&intersection), &va & &vb);
+        assert_eq!(values(&difference), &va - &vb);
+    }
+}
@@ -0,0 +1,499 @@
+//! Images and their encoding as PNG or animated GIF, without dependencies.
+
+use std::{collections::HashMap, fmt, str::FromStr, time::Duration};
+
+use super::grid::Grid;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+/// Parses `#rrggbb`, the `#` being optional.
+impl FromStr for Rgb {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hex = s.strip_prefix('#').unwrap_or(s);
+        let channel = |i: usize| {
+            hex.get(i..i + 2)
+                .and_then(|c| u8::from_str_radix(c, 16).ok())
+        };
+        match (hex.len(), channel(0), channel(2), channel(4)) {
+            (6, Some(r), Some(g), Some(b)) => Ok(Rgb(r, g, b)),
+            _ => Err(format!("expected a colour like #c8a050, found {:?}", s)),
+        }
+    }
+}
+
+impl fmt::Display for Rgb {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+    }
+}
+
+impl Rgb {
+    /// The colour a terminal shows for code `code` of its 256 colour palette,
+    /// with the usual xterm values.
+    pub fn from_ansi256(code: u8) -> Rgb {
+        const SYSTEM: [Rgb; 16] = [
+            Rgb(0x00, 0x00, 0x00),
+            Rgb(0x80, 0x00, 0x00),
+            Rgb(0x00, 0x80, 0x00),
+            Rgb(0x80, 0x80, 0x00),
+            Rgb(0x00, 0x00, 0x80),
+            Rgb(0x80, 0x00, 0x80),
+            Rgb(0x00, 0x80, 0x80),
+            Rgb(0xc0, 0xc0, 0xc0),
+            Rgb(0x80, 0x80, 0x80),
+            Rgb(0xff, 0x00, 0x00),
+            Rgb(0x00, 0xff, 0x00),
+            Rgb(0xff, 0xff, 0x00),
+            Rgb(0x00, 0x00, 0xff),
+            Rgb(0xff, 0x00, 0xff),
+            Rgb(0x00, 0xff, 0xff),
+            Rgb(0xff, 0xff, 0xff),
+        ];
+        const LEVELS: [u8; 6] = [0x00, 0x5f, 0x87, 0xaf, 0xd7, 0xff];
+        match code {
+            0..=15 => SYSTEM[code as usize],
+            16..=231 => {
+                let i = (code - 16) as usize;
+                Rgb(LEVELS[i / 36], LEVELS[i / 6 % 6], LEVELS[i % 6])
+            }
+            _ => {
+                let level = 8 + 10 * (code - 232);
+                Rgb(level, level, level)
+            }
+        }
+    }
+}
+
+/// A picture, pixel by pixel and row by row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<Rgb>,
+}
+
+impl Image {
+    pub fn new(width: usize, height: usize, fill: Rgb) -> Self {
+        Image {
+            width,
+            height,
+            pixels: vec![fill; width * height],
+        }
+    }
+
+    /// Draws each cell of `grid` as a `scale` by `scale` square.
+    pub fn from_grid<T>(grid: &Grid<T>, scale: usize, color: impl Fn(&T) -> Rgb) -> Self {
+        let mut image = Image::new(grid.width() * scale, grid.height() * scale, Rgb::default());
+        for ((x, y), cell) in grid.iter() {
+            image.fill_cell(x, y, scale, color(cell));
+        }
+        image
+    }
+
+    #[allow(dead_code)]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    #[allow(dead_code)]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    #[allow(dead_code)]
+    pub fn get(&self, x: usize, y: usize) -> Option<Rgb> {
+        (x < self.width && y < self.height).then(|| self.pixels[y * self.width + x])
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, c: Rgb) {
+        if x < self.width && y < self.height {
+            self.pixels[y * self.width + x] = c;
+        }
+    }
+
+    /// Fills the `scale` by `scale` square of cell `(x, y)`, as drawn by
+    /// [`Image::from_grid`].
+    pub fn fill_cell(&mut self, x: usize, y: usize, scale: usize, c: Rgb) {
+        for dy in 0..scale {
+            for dx in 0..scale {
+                self.set(x * scale + dx, y * scale + dy, c);
+            }
+        }
+    }
+
+    pub fn to_png(&self) -> Vec<u8> {
+        let mut raw = Vec::with_capacity((self.width * 3 + 1) * self.height);
+        for row in self.pixels.chunks(self.width.max(1)) {
+            // filter type: none
+            raw.push(0);
+            raw.extend(row.iter().flat_map(|p| [p.0, p.1, p.2]));
+        }
+
+        let mut header = Vec::new();
+        header.extend((self.width as u32).to_be_bytes());
+        header.extend((self.height as u32).to_be_bytes());
+        // 8 bits per channel, truecolour, deflate, no filter, no interlace
+        header.extend([8, 2, 0, 0, 0]);
+
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        png_chunk(&mut png, b"IHDR", &header);
+        png_chunk(&mut png, b"IDAT", &zlib_stored(&raw));
+        png_chunk(&mut png, b"IEND", &[]);
+        png
+    }
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &b in bytes {
+        crc ^= b as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in bytes {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
+
+fn png_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend((data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend(kind);
+    png.extend(data);
+    let crc = crc32(&png[start..]);
+    png.extend(crc.to_be_bytes());
+}
+
+/// A zlib stream made of uncompressed deflate blocks: larger files, but
+/// nothing to get wrong.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x78, 0x01];
+    let mut blocks = data.chunks(0xffff).peekable();
+    if blocks.peek().is_none() {
+        out.extend([1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        out.push(blocks.peek().is_none() as u8);
+        let len = block.len() as u16;
+        out.extend(len.to_le_bytes());
+        out.extend((!len).to_le_bytes());
+        out.extend(block);
+    }
+    out.extend(adler32(data).to_be_bytes());
+    out
+}
+
+/// An animated GIF, looping forever, whose frames share one palette of at
+/// most 256 colours.
+#[derive(Debug, Clone)]
+pub struct Gif {
+    width: usize,
+    height: usize,
+    palette: Vec<Rgb>,
+    indices: HashMap<Rgb, u8>,
+    /// Palette indices of each frame, with its delay in hundredths of a second.
+    frames: Vec<(Vec<u8>, u16)>,
+}
+
+impl Gif {
+    pub fn new(width: usize, height: usize) -> Self {
+        Gif {
+            width,
+            height,
+            palette: Vec::new(),
+            indices: HashMap::new(),
+            frames: Vec::new(),
+        }
+    }
+
+    /// Adds `image`, shown for `delay`. Panics if it doesn't have the size of
+    /// the animation, or brings the colour count over 256.
+    pub fn push_frame(&mut self, image: &Image, delay: Duration) {
+        assert_eq!(
+            (image.width, image.height),
+            (self.width, self.height),
+            "frame size"
+        );
+        let pixels = image
+            .pixels
+            .iter()
+            .map(|c| {
+                *self.indices.entry(*c).or_insert_with(|| {
+                    assert!(self.palette.len() < 256, "more than 256 colours");
+                    self.palette.push(*c);
+                    (self.palette.len() - 1) as u8
+                })
+            })
+            .collect();
+        let delay = (delay.as_millis() / 10).min(u16::MAX as u128) as u16;
+        self.frames.push((pixels, delay));
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        // the palette size is a power of 2, at least 2
+        let bits = (1..=8).find(|b| 1 << b >= self.palette.len()).unwrap_or(8);
+
+        let mut gif = b"GIF89a".to_vec();
+        gif.extend((self.width as u16).to_le_bytes());
+        gif.extend((self.height as u16).to_le_bytes());
+        // global colour table of 2^bits entries, background 0, no aspect ratio
+        gif.extend([0x80 | (bits - 1) as u8, 0, 0]);
+        for i in 0..1 << bits {
+            let c = self.palette.get(i).copied().unwrap_or_default();
+            gif.extend([c.0, c.1, c.2]);
+        }
+        // loop forever
+        gif.extend(b"\x21\xff\x0bNETSCAPE2.0\x03\x01\x00\x00\x00");
+
+        for (pixels, delay) in self.frames.iter() {
+            gif.extend([0x21, 0xf9, 4, 0]);
+            gif.extend(delay.to_le_bytes());
+            gif.extend([0, 0]);
+
+            gif.push(0x2c);
+            gif.extend([0, 0, 0, 0]);
+            gif.extend((self.width as u16).to_le_bytes());
+            gif.extend((self.height as u16).to_le_bytes());
+            gif.push(0);
+
+            let min_code_size = bits.max(2) as u8;
+            gif.push(min_code_size);
+            for block in lzw(pixels, min_code_size).chunks(255) {
+                gif.push(block.len() as u8);
+                gif.extend(block);
+            }
+            gif.push(0);
+        }
+
+        gif.push(0x3b);
+        gif
+    }
+}
+
+/// Packs codes of varying widths, least significant bit first.
+struct BitWriter {
+    bytes: Vec<u8>,
+    acc: u32,
+    len: u32,
+}
+
+impl BitWriter {
+    fn write(&mut self, code: u16, width: u32) {
+        self.acc |= (code as u32) << self.len;
+        self.len += width;
+        while self.len >= 8 {
+            self.bytes.push(self.acc as u8);
+            self.acc >>= 8;
+            self.len -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.len > 0 {
+            self.bytes.push(self.acc as u8);
+        }
+        self.bytes
+    }
+}
+
+/// GIF flavoured LZW compression of palette indices.
+fn lzw(pixels: &[u8], min_code_size: u8) -> Vec<u8> {
+    const MAX_CODES: u16 = 4096;
+    let clear = 1u16 << min_code_size;
+    let end = clear + 1;
+
+    let mut out = BitWriter {
+        bytes: Vec::new(),
+        acc: 0,
+        len: 0,
+    };
+    let mut width = min_code_size as u32 + 1;
+    let mut codes: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut next = end + 1;
+    out.write(clear, width);
+
+    let Some((&first, rest)) = pixels.split_first() else {
+        out.write(end, width);
+        return out.finish();
+    };
+    let mut prefix = first as u16;
+    for &p in rest {
+        if let Some(&code) = codes.get(&(prefix, p)) {
+            prefix = code;
+            continue;
+        }
+        out.write(prefix, width);
+        if next == MAX_CODES {
+            out.write(clear, width);
+            codes.clear();
+            next = end + 1;
+            width = min_code_size as u32 + 1;
+        } else {
+            codes.insert((prefix, p), next);
+            // the decoder learns each code one step later, and widens then
+            if next == 1 << width && width < 12 {
+                width += 1;
+            }
+            next += 1;
+        }
+        prefix = p as u16;
+    }
+    out.write(prefix, width);
+    out.write(end, width);
+    out.finish()
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::{adler32, crc32, lzw, Gif, Image, Rgb};
+    use crate::utils::grid::Grid;
+
+    /// A straightforward GIF LZW decoder, to check the encoder against.
+    fn unlzw(bytes: &[u8], min_code_size: u8) -> Vec<u8> {
+        let clear = 1usize << min_code_size;
+        let end = clear + 1;
+        let mut table: Vec<Vec<u8>> = Vec::new();
+        let reset = |table: &mut Vec<Vec<u8>>| {
+            *table = (0..clear).map(|i| vec![i as u8]).collect();
+            table.push(Vec::new());
+            table.push(Vec::new());
+        };
+        reset(&mut table);
+
+        let (mut acc, mut len, mut pos) = (0u32, 0u32, 0);
+        let mut width = min_code_size as u32 + 1;
+        let mut previous: Option<Vec<u8>> = None;
+        let mut out = Vec::new();
+        loop {
+            while len < width {
+                acc |= (bytes[pos] as u32) << len;
+                pos += 1;
+                len += 8;
+            }
+            let code = (acc & ((1 << width) - 1)) as usize;
+            acc >>= width;
+            len -= width;
+
+            if code == clear {
+                reset(&mut table);
+                width = min_code_size as u32 + 1;
+                previous = None;
+                continue;
+            }
+            if code == end {
+                return out;
+            }
+            let entry = match (table.get(code), &previous) {
+                (Some(entry), _) => entry.clone(),
+                (None, Some(prev)) => [prev.clone(), vec![prev[0]]].concat(),
+                (None, None) => panic!("unknown first code {}", code),
+            };
+            if let Some(prev) = previous {
+                if table.len() < 4096 {
+                    table.push([prev, vec![entry[0]]].concat());
+                }
+            }
+            if table.len() == 1 << width && width < 12 {
+                width += 1;
+            }
+            out.extend(&entry);
+            previous = Some(entry);
+        }
+    }
+
+    #[test]
+    fn test_checksums() {
+        assert_eq!(crc32(b"IEND"), 0xae42_6082);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+    }
+
+    #[test]
+    fn test_lzw() {
+        let mut state = 7u32;
+        let noise: Vec<u8> = (0..20_000)
+            .map(|_| {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                (state >> 16) as u8 % 5
+            })
+            .collect();
+        let runs: Vec<u8> = (0..30_000).map(|i| (i / 700 % 4) as u8).collect();
+        let bytes: Vec<u8> = (0..=255).cycle().take(10_000).collect();
+
+        for (pixels, bits) in [(noise, 3), (runs, 2), (bytes, 8), (vec![], 2), (vec![1], 2)] {
+            assert_eq!(unlzw(&lzw(&pixels, bits), bits), pixels);
+        }
+    }
+
+    #[test]
+    fn test_png() {
+        let grid = Grid::parse(".@\n@.\n", ".@").unwrap();
+        let gold = Rgb(200, 160, 80);
+        let image = Image::from_grid(&grid, 3, |c| if *c == '@' { gold } else { Rgb(0, 0, 0) });
+        assert_eq!((image.width(), image.height()), (6, 6));
+        assert_eq!(image.get(4, 1), Some(gold));
+        assert_eq!(image.get(1, 4), Some(gold));
+        assert_eq!(image.get(4, 4), Some(Rgb(0, 0, 0)));
+
+        let png = image.to_png();
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR\0\0\0\x06\0\0\0\x06"));
+        assert!(png.ends_with(b"IEND\xae\x42\x60\x82"));
+    }
+
+    #[test]
+    fn test_gif() {
+        let mut gif = Gif::new(4, 2);
+        let mut image = Image::new(4, 2, Rgb(0, 0, 0));
+        gif.push_frame(&image, Duration::from_millis(250));
+        image.set(1, 1, "#ff0000".parse().unwrap());
+        image.set(2, 0, Rgb(0, 0, 255));
+        gif.push_frame(&image, Duration::from_millis(250));
+        assert_eq!(gif.frame_count(), 2);
+
+        let bytes = gif.encode();
+        // black, then blue and red in the order they appear
+        assert!(bytes.starts_with(b"GIF89a\x04\0\x02\0\x81\0\0\0\0\0\0\0\xff\xff\0\0\0\0\0"));
+        let pixels = lzw(&[0, 0, 1, 0, 0, 2, 0, 0], 2);
+        let last_frame = [&[2, pixels.len() as u8], &pixels[..], &[0, 0x3b]].concat();
+        assert!(bytes.ends_with(&last_frame));
+        assert_eq!(unlzw(&pixels, 2), vec![0, 0, 1, 0, 0, 2, 0, 0]);
+    }
+
+    #[test]
+    fn test_from_ansi256() {
+        assert_eq!(Rgb::from_ansi256(9), Rgb(255, 0, 0));
+        assert_eq!(Rgb::from_ansi256(16), Rgb(0, 0, 0));
+        assert_eq!(Rgb::from_ansi256(208), Rgb(0xff, 0x87, 0x00));
+        assert_eq!(Rgb::from_ansi256(231), Rgb(255, 255, 255));
+        assert_eq!(Rgb::from_ansi256(244), Rgb(128, 128, 128));
+    }
+
+    #[test]
+    fn test_parse_rgb() {
+        assert_eq!("#c8a050".parse(), Ok(Rgb(200, 160, 80)));
+        assert_eq!("C8A050".parse(), Ok(Rgb(200, 160, 80)));
+        assert!("#c8a05".parse::<Rgb>().is_err());
+        assert!("#c8a05g".parse::<Rgb>().is_err());
+        assert_eq!(Rgb(200, 160, 80).to_string(), "#c8a050");
+    }
+}
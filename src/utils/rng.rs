@@ -0,0 +1,25 @@
+//! A small deterministic generator for randomized tests.
+
+/// Marsaglia's xorshift64: fast, reproducible, and random enough to build
+/// test cases.
+pub struct XorShift(u64);
+
+impl XorShift {
+    /// `seed` must not be 0, which would only ever give 0.
+    pub fn new(seed: u64) -> Self {
+        assert_ne!(seed, 0);
+        XorShift(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// A number in `0..n`, near enough to uniform for small `n`.
+    pub fn below(&mut self, n: u64) -> u64 {
+        self.next_u64() % n
+    }
+}
@@ -0,0 +1,226 @@
+//! Graph searches over implicit graphs: states are expanded on demand
+//! through a neighbour function, and each is visited at most once.
+
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, VecDeque},
+    hash::Hash,
+    ops::Add,
+};
+
+/// What a search found, and how much work it took.
+#[derive(Debug, Clone)]
+pub struct Outcome<S, C> {
+    /// The goal reached and the cost of reaching it, if any.
+    #[allow(dead_code)]
+    pub goal: Option<(S, C)>,
+    /// The number of states taken off the frontier to look at their neighbours.
+    #[allow(dead_code)]
+    pub expanded: usize,
+    /// Every state reached, with the index of the one it was reached from.
+    nodes: Vec<(S, usize)>,
+    goal_node: Option<usize>,
+}
+
+impl<S: Clone, C> Outcome<S, C> {
+    /// The number of distinct states reached, expanded or not.
+    #[allow(dead_code)]
+    pub fn visited(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// The states from the start to the goal, both included.
+    #[allow(dead_code)]
+    pub fn path(&self) -> Option<Vec<S>> {
+        let mut node = self.goal_node?;
+        let mut path = vec![self.nodes[node].0.clone()];
+        while node != 0 {
+            node = self.nodes[node].1;
+            path.push(self.nodes[node].0.clone());
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+/// The goal closest to `start` in number of steps.
+#[allow(dead_code)]
+pub fn bfs<S, I>(
+    start: S,
+    mut neighbours: impl FnMut(&S) -> I,
+    mut is_goal: impl FnMut(&S) -> bool,
+) -> Outcome<S, usize>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = S>,
+{
+    let mut nodes = vec![(start.clone(), 0)];
+    let mut steps = vec![0];
+    let mut visited = HashMap::from([(start, 0)]);
+    let mut todo = VecDeque::from([0]);
+    let mut expanded = 0;
+
+    while let Some(node) = todo.pop_front() {
+        if is_goal(&nodes[node].0) {
+            return Outcome {
+                goal: Some((nodes[node].0.clone(), steps[node])),
+                expanded,
+                nodes,
+                goal_node: Some(node),
+            };
+        }
+        expanded += 1;
+        for next in neighbours(&nodes[node].0) {
+            if !visited.contains_key(&next) {
+                visited.insert(next.clone(), nodes.len());
+                todo.push_back(nodes.len());
+                nodes.push((next, node));
+                steps.push(steps[node] + 1);
+            }
+        }
+    }
+
+    Outcome {
+        goal: None,
+        expanded,
+        nodes,
+        goal_node: None,
+    }
+}
+
+/// The goal reachable from `start` at the lowest cost, where `neighbours`
+/// gives each next state with the cost of the step to it.
+#[allow(dead_code)]
+pub fn dijkstra<S, C, I>(
+    start: S,
+    neighbours: impl FnMut(&S) -> I,
+    is_goal: impl FnMut(&S) -> bool,
+) -> Outcome<S, C>
+where
+    S: Clone + Eq + Hash,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (S, C)>,
+{
+    astar(start, neighbours, |_| C::default(), is_goal)
+}
+
+/// Same as [`dijkstra`], guided by `heuristic`, which must never
+/// overestimate the cost left to reach a goal for the result to be the best.
+pub fn astar<S, C, I>(
+    start: S,
+    mut neighbours: impl FnMut(&S) -> I,
+    mut heuristic: impl FnMut(&S) -> C,
+    mut is_goal: impl FnMut(&S) -> bool,
+) -> Outcome<S, C>
+where
+    S: Clone + Eq + Hash,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (S, C)>,
+{
+    let zero = C::default();
+    let mut nodes = vec![(start.clone(), 0)];
+    // the lowest cost found so far for each state, and its node
+    let mut best = HashMap::from([(start.clone(), (zero, 0))]);
+    let mut todo = BinaryHeap::from([Reverse((heuristic(&start), zero, 0))]);
+    let mut expanded = 0;
+
+    while let Some(Reverse((_, cost, node))) = todo.pop() {
+        let state = nodes[node].0.clone();
+        // a better way to this state was found after this one was queued
+        if best[&state].1 != node {
+            continue;
+        }
+        if is_goal(&state) {
+            return Outcome {
+                goal: Some((state, cost)),
+                expanded,
+                nodes,
+                goal_node: Some(node),
+            };
+        }
+        expanded += 1;
+        for (next, step) in neighbours(&state) {
+            let next_cost = cost + step;
+            if best.get(&next).is_some_and(|&(c, _)| c <= next_cost) {
+                continue;
+            }
+            let estimate = next_cost + heuristic(&next);
+            best.insert(next.clone(), (next_cost, nodes.len()));
+            todo.push(Reverse((estimate, next_cost, nodes.len())));
+            nodes.push((next, node));
+        }
+    }
+
+    Outcome {
+        goal: None,
+        expanded,
+        nodes,
+        goal_node: None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{astar, bfs, dijkstra};
+    use crate::utils::grid::{Grid, Pos};
+
+    const MAZE: &str = "S..#....\n.#.#.##.\n.#...#..\n.####.#.\n......#E\n";
+
+    fn maze() -> (Grid<char>, Pos, Pos) {
+        let grid = Grid::parse(MAZE, ".#SE").unwrap();
+        let (start, end) = (grid.find('S').unwrap(), grid.find('E').unwrap());
+        (grid, start, end)
+    }
+
+    fn open(grid: &Grid<char>, pos: Pos) -> Vec<Pos> {
+        grid.neighbours_4(pos)
+            .filter(|p| grid.get(*p) != Some(&'#'))
+            .collect()
+    }
+
+    #[test]
+    fn test_bfs() {
+        let (grid, start, end) = maze();
+        let outcome = bfs(start, |&p| open(&grid, p), |&p| p == end);
+        assert_eq!(outcome.goal, Some((end, 15)));
+
+        let path = outcome.path().unwrap();
+        assert_eq!(path.len(), 16);
+        assert_eq!((path[0], path[15]), (start, end));
+        assert!(path
+            .windows(2)
+            .all(|w| w[0].0.abs_diff(w[1].0) + w[0].1.abs_diff(w[1].1) == 1));
+
+        let outcome = bfs(start, |&p| open(&grid, p), |_| false);
+        assert_eq!(outcome.goal, None);
+        assert_eq!(outcome.path(), None);
+        assert_eq!(outcome.visited(), grid.positions(|c| *c != '#').count());
+    }
+
+    #[test]
+    fn test_dijkstra() {
+        // going right costs 1, any other way costs 3
+        let (grid, start, end) = maze();
+        let weighted = |&p: &Pos| {
+            open(&grid, p)
+                .into_iter()
+                .map(move |q| (q, if q.0 > p.0 { 1 } else { 3 }))
+        };
+        let outcome = dijkstra(start, weighted, |&p| p == end);
+        assert_eq!(outcome.goal, Some((end, 8 * 3 + 7)));
+        assert_eq!(outcome.path().unwrap().len(), 16);
+    }
+
+    #[test]
+    fn test_astar() {
+        let (grid, start, end) = maze();
+        let steps = |&p: &Pos| open(&grid, p).into_iter().map(|q| (q, 1));
+        let distance = |p: &Pos| p.0.abs_diff(end.0) + p.1.abs_diff(end.1);
+
+        let guided = astar(start, steps, distance, |&p| p == end);
+        let blind = dijkstra(start, steps, |&p| p == end);
+        assert_eq!(guided.goal, Some((end, 15)));
+        assert_eq!(blind.goal, guided.goal);
+        assert!(guided.expanded <= blind.expanded);
+    }
+}